@@ -7,6 +7,7 @@ mod encryption_type_mismatch;
 mod entity_too_small;
 mod incomplete_body;
 mod internal_error;
+mod invalid_argument;
 mod invalid_digest;
 mod invalid_object_state;
 mod invalid_part;
@@ -14,6 +15,7 @@ mod invalid_part_order;
 mod invalid_range;
 mod invalid_region;
 mod invalid_tag;
+mod invalid_target_bucket_for_logging;
 mod invalid_write_offset;
 mod malformed_xml;
 mod method_not_allowed;
@@ -25,6 +27,8 @@ mod no_such_version;
 mod not_implemented;
 mod operation_aborted;
 mod precondition_failed;
+mod quota_exceeded;
+mod slow_down;
 mod too_many_parts;
 
 pub use access_denied::AccessDeniedOutput;
@@ -36,6 +40,7 @@ pub use encryption_type_mismatch::EncryptionTypeMismatchOutput;
 pub use entity_too_small::EntityTooSmallOutput;
 pub use incomplete_body::IncompleteBodyOutput;
 pub use internal_error::InternalErrorOutput;
+pub use invalid_argument::InvalidArgumentOutput;
 pub use invalid_digest::InvalidDigestOutput;
 pub use invalid_object_state::InvalidObjectStateOutput;
 pub use invalid_part::InvalidPartOutput;
@@ -43,6 +48,7 @@ pub use invalid_part_order::InvalidPartOrderOutput;
 pub use invalid_range::InvalidRangeOutput;
 pub use invalid_region::InvalidRegionOutput;
 pub use invalid_tag::InvalidTagOutput;
+pub use invalid_target_bucket_for_logging::InvalidTargetBucketForLoggingOutput;
 pub use invalid_write_offset::InvalidWriteOffsetOutput;
 pub use malformed_xml::MalformedXMLOutput;
 pub use method_not_allowed::MethodNotAllowedOutput;
@@ -54,4 +60,6 @@ pub use no_such_version::NoSuchVersionOutput;
 pub use not_implemented::NotImplementedOutput;
 pub use operation_aborted::OperationAbortedOutput;
 pub use precondition_failed::PreconditionFailedOutput;
+pub use quota_exceeded::QuotaExceededOutput;
+pub use slow_down::SlowDownOutput;
 pub use too_many_parts::TooManyPartsOutput;
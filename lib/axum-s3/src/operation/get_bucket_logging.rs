@@ -0,0 +1,28 @@
+use axum::extract::FromRequest;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum_derive_macros::IntoResponse;
+use axum_header::Header;
+use axum_serde::Xml;
+use bon::Builder;
+use serde_s3::operation::GetBucketLoggingInputHeader;
+use serde_s3::operation::GetBucketLoggingInputPath;
+use serde_s3::operation::GetBucketLoggingOutputBody;
+
+#[derive(Debug, FromRequest)]
+pub struct GetBucketLoggingInput {
+    #[from_request(via(Path))]
+    pub path: GetBucketLoggingInputPath,
+
+    #[from_request(via(Header))]
+    pub header: GetBucketLoggingInputHeader,
+}
+
+#[derive(Debug, Builder, IntoResponse)]
+pub struct GetBucketLoggingOutput {
+    #[builder(default = StatusCode::OK)]
+    pub status: StatusCode,
+
+    #[into_response(via(Xml))]
+    pub body: GetBucketLoggingOutputBody,
+}
@@ -7,6 +7,7 @@ mod delete_bucket_tagging;
 mod delete_object;
 mod delete_object_tagging;
 mod get_bucket_location;
+mod get_bucket_logging;
 mod get_bucket_tagging;
 mod get_bucket_versioning;
 mod get_object;
@@ -19,10 +20,12 @@ mod list_object_versions;
 mod list_objects;
 mod list_objects_v2;
 mod list_parts;
+mod put_bucket_logging;
 mod put_bucket_tagging;
 mod put_bucket_versioning;
 mod put_object;
 mod put_object_tagging;
+mod rename_object;
 mod upload_part;
 
 pub use abort_multipart_upload::AbortMultipartUploadInput;
@@ -43,6 +46,8 @@ pub use delete_object_tagging::DeleteObjectTaggingInput;
 pub use delete_object_tagging::DeleteObjectTaggingOutput;
 pub use get_bucket_location::GetBucketLocationInput;
 pub use get_bucket_location::GetBucketLocationOutput;
+pub use get_bucket_logging::GetBucketLoggingInput;
+pub use get_bucket_logging::GetBucketLoggingOutput;
 pub use get_bucket_tagging::GetBucketTaggingInput;
 pub use get_bucket_tagging::GetBucketTaggingOutput;
 pub use get_bucket_versioning::GetBucketVersioningInput;
@@ -67,6 +72,8 @@ pub use list_objects_v2::ListObjectsV2Input;
 pub use list_objects_v2::ListObjectsV2Output;
 pub use list_parts::ListPartsInput;
 pub use list_parts::ListPartsOutput;
+pub use put_bucket_logging::PutBucketLoggingInput;
+pub use put_bucket_logging::PutBucketLoggingOutput;
 pub use put_bucket_tagging::PutBucketTaggingInput;
 pub use put_bucket_tagging::PutBucketTaggingOutput;
 pub use put_bucket_versioning::PutBucketVersioningInput;
@@ -75,5 +82,7 @@ pub use put_object::PutObjectInput;
 pub use put_object::PutObjectOutput;
 pub use put_object_tagging::PutObjectTaggingInput;
 pub use put_object_tagging::PutObjectTaggingOutput;
+pub use rename_object::RenameObjectInput;
+pub use rename_object::RenameObjectOutput;
 pub use upload_part::UploadPartInput;
 pub use upload_part::UploadPartOutput;
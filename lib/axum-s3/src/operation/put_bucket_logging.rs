@@ -0,0 +1,28 @@
+use axum::extract::FromRequest;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum_derive_macros::IntoResponse;
+use axum_header::Header;
+use axum_serde::Xml;
+use bon::Builder;
+use serde_s3::operation::PutBucketLoggingInputBody;
+use serde_s3::operation::PutBucketLoggingInputHeader;
+use serde_s3::operation::PutBucketLoggingInputPath;
+
+#[derive(Debug, FromRequest)]
+pub struct PutBucketLoggingInput {
+    #[from_request(via(Path))]
+    pub path: PutBucketLoggingInputPath,
+
+    #[from_request(via(Header))]
+    pub header: PutBucketLoggingInputHeader,
+
+    #[from_request(via(Xml))]
+    pub body: PutBucketLoggingInputBody,
+}
+
+#[derive(Debug, Builder, IntoResponse)]
+pub struct PutBucketLoggingOutput {
+    #[builder(default = StatusCode::OK)]
+    pub status: StatusCode,
+}
@@ -0,0 +1,23 @@
+use axum::extract::FromRequest;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum_derive_macros::IntoResponse;
+use axum_header::Header;
+use bon::Builder;
+use serde_s3::operation::RenameObjectInputHeader;
+use serde_s3::operation::RenameObjectInputPath;
+
+#[derive(Debug, FromRequest)]
+pub struct RenameObjectInput {
+    #[from_request(via(Path))]
+    pub path: RenameObjectInputPath,
+
+    #[from_request(via(Header))]
+    pub header: RenameObjectInputHeader,
+}
+
+#[derive(Debug, Builder, IntoResponse)]
+pub struct RenameObjectOutput {
+    #[builder(default = StatusCode::OK)]
+    pub status: StatusCode,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use serde_rename_chain::serde_rename_chain;
+
+use crate::types::BucketLoggingStatus;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetBucketLoggingInputPath {
+    pub bucket: String,
+}
+
+#[serde_rename_chain(add_prefix = "x_amz_", convert_case = "kebab")]
+#[derive(Debug, Deserialize)]
+pub struct GetBucketLoggingInputHeader {
+    pub expected_bucket_owner: Option<String>,
+}
+
+pub type GetBucketLoggingOutputBody = BucketLoggingStatus;
@@ -7,6 +7,7 @@ mod delete_bucket_tagging;
 mod delete_object;
 mod delete_object_tagging;
 mod get_bucket_location;
+mod get_bucket_logging;
 mod get_bucket_tagging;
 mod get_bucket_versioning;
 mod get_object;
@@ -19,10 +20,12 @@ mod list_object_versions;
 mod list_objects;
 mod list_objects_v2;
 mod list_parts;
+mod put_bucket_logging;
 mod put_bucket_tagging;
 mod put_bucket_versioning;
 mod put_object;
 mod put_object_tagging;
+mod rename_object;
 mod upload_part;
 
 pub use abort_multipart_upload::AbortMultipartUploadInputHeader;
@@ -58,6 +61,9 @@ pub use delete_object_tagging::DeleteObjectTaggingOutputHeader;
 pub use get_bucket_location::GetBucketLocationInputHeader;
 pub use get_bucket_location::GetBucketLocationInputPath;
 pub use get_bucket_location::GetBucketLocationOutputBody;
+pub use get_bucket_logging::GetBucketLoggingInputHeader;
+pub use get_bucket_logging::GetBucketLoggingInputPath;
+pub use get_bucket_logging::GetBucketLoggingOutputBody;
 pub use get_bucket_tagging::GetBucketTaggingInputHeader;
 pub use get_bucket_tagging::GetBucketTaggingInputPath;
 pub use get_bucket_tagging::GetBucketTaggingOutputBody;
@@ -107,6 +113,9 @@ pub use list_parts::ListPartsInputPath;
 pub use list_parts::ListPartsInputQuery;
 pub use list_parts::ListPartsOutputBody;
 pub use list_parts::ListPartsOutputHeader;
+pub use put_bucket_logging::PutBucketLoggingInputBody;
+pub use put_bucket_logging::PutBucketLoggingInputHeader;
+pub use put_bucket_logging::PutBucketLoggingInputPath;
 pub use put_bucket_tagging::PutBucketTaggingInputBody;
 pub use put_bucket_tagging::PutBucketTaggingInputHeader;
 pub use put_bucket_tagging::PutBucketTaggingInputPath;
@@ -121,6 +130,8 @@ pub use put_object_tagging::PutObjectTaggingInputHeader;
 pub use put_object_tagging::PutObjectTaggingInputPath;
 pub use put_object_tagging::PutObjectTaggingInputQuery;
 pub use put_object_tagging::PutObjectTaggingOutputHeader;
+pub use rename_object::RenameObjectInputHeader;
+pub use rename_object::RenameObjectInputPath;
 pub use upload_part::UploadPartInputHeader;
 pub use upload_part::UploadPartInputPath;
 pub use upload_part::UploadPartInputQuery;
@@ -51,6 +51,9 @@ pub struct PutObjectInputHeader {
     #[serde_rename_chain(convert_case = "train")]
     pub content_language: Option<String>,
 
+    #[serde_rename_chain(convert_case = "train")]
+    pub content_length: Option<u64>,
+
     #[serde(rename = "Content-MD5")]
     #[serde_as(as = "Option<DisplayFromBytes>")]
     pub content_md5: Option<DigestMd5>,
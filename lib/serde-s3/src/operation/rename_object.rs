@@ -0,0 +1,49 @@
+use httpdate::HttpDate;
+use serde_rename_chain::serde_rename_chain;
+use serde_with::serde_as;
+use serde_with_extra::DisplayFromBytes;
+use serdev::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Validate, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(validate = "Validate::validate")]
+pub struct RenameObjectInputPath {
+    pub bucket: String,
+
+    #[validate(length(min = 1))]
+    pub key: String,
+}
+
+#[serde_as]
+#[serde_rename_chain(add_prefix = "x_amz_", convert_case = "kebab")]
+#[derive(Debug, Deserialize)]
+pub struct RenameObjectInputHeader {
+    #[serde_rename_chain(convert_case = "train")]
+    pub if_match: Option<String>,
+
+    #[serde_as(as = "Option<DisplayFromBytes>")]
+    #[serde_rename_chain(convert_case = "train")]
+    pub if_modified_since: Option<HttpDate>,
+
+    #[serde_rename_chain(convert_case = "train")]
+    pub if_none_match: Option<String>,
+
+    #[serde_as(as = "Option<DisplayFromBytes>")]
+    #[serde_rename_chain(convert_case = "train")]
+    pub if_unmodified_since: Option<HttpDate>,
+
+    pub client_token: Option<String>,
+
+    pub rename_source: String,
+
+    pub rename_source_if_match: Option<String>,
+
+    #[serde_as(as = "Option<DisplayFromBytes>")]
+    pub rename_source_if_modified_since: Option<HttpDate>,
+
+    pub rename_source_if_none_match: Option<String>,
+
+    #[serde_as(as = "Option<DisplayFromBytes>")]
+    pub rename_source_if_unmodified_since: Option<HttpDate>,
+}
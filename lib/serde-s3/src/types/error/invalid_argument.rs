@@ -6,10 +6,11 @@ use stringify_checked::stringify_ty;
 #[skip_serializing_none]
 #[derive(Debug, Builder, Serialize)]
 #[serde(rename = "Error", rename_all = "PascalCase")]
-struct InvalidArgument {
+pub struct InvalidArgument {
     #[builder(default = stringify_ty!(InvalidArgument))]
     pub code: &'static str,
 
+    #[builder(default = "Invalid Argument")]
     pub message: &'static str,
 
     pub resource: Option<String>,
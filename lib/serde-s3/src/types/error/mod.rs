@@ -16,6 +16,7 @@ mod invalid_range;
 mod invalid_region;
 mod invalid_request;
 mod invalid_tag;
+mod invalid_target_bucket_for_logging;
 mod invalid_write_offset;
 mod malformed_xml;
 mod method_not_allowed;
@@ -27,6 +28,8 @@ mod no_such_version;
 mod not_implemented;
 mod operation_aborted;
 mod precondition_failed;
+mod quota_exceeded;
+mod slow_down;
 mod too_many_parts;
 
 pub use access_denied::AccessDenied;
@@ -38,6 +41,7 @@ pub use encryption_type_mismatch::EncryptionTypeMismatch;
 pub use entity_too_small::EntityTooSmall;
 pub use incomplete_body::IncompleteBody;
 pub use internal_error::InternalError;
+pub use invalid_argument::InvalidArgument;
 pub use invalid_digest::InvalidDigest;
 pub use invalid_object_state::InvalidObjectState;
 pub use invalid_part::InvalidPart;
@@ -45,6 +49,7 @@ pub use invalid_part_order::InvalidPartOrder;
 pub use invalid_range::InvalidRange;
 pub use invalid_region::InvalidRegion;
 pub use invalid_tag::InvalidTag;
+pub use invalid_target_bucket_for_logging::InvalidTargetBucketForLogging;
 pub use invalid_write_offset::InvalidWriteOffset;
 pub use malformed_xml::MalformedXML;
 pub use method_not_allowed::MethodNotAllowed;
@@ -56,4 +61,6 @@ pub use no_such_version::NoSuchVersion;
 pub use not_implemented::NotImplemented;
 pub use operation_aborted::OperationAborted;
 pub use precondition_failed::PreconditionFailed;
+pub use quota_exceeded::QuotaExceeded;
+pub use slow_down::SlowDown;
 pub use too_many_parts::TooManyParts;
@@ -0,0 +1,19 @@
+use bon::Builder;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use stringify_checked::stringify_ty;
+
+#[skip_serializing_none]
+#[derive(Debug, Builder, Serialize)]
+#[serde(rename = "Error", rename_all = "PascalCase")]
+pub struct QuotaExceeded {
+    #[builder(default = stringify_ty!(QuotaExceeded))]
+    pub code: &'static str,
+
+    #[builder(default = "Your request would exceed the storage quota of the bucket or its owner.")]
+    pub message: &'static str,
+
+    pub resource: Option<String>,
+
+    pub request_id: Option<String>,
+}
@@ -0,0 +1,19 @@
+use bon::Builder;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use stringify_checked::stringify_ty;
+
+#[skip_serializing_none]
+#[derive(Debug, Builder, Serialize)]
+#[serde(rename = "Error", rename_all = "PascalCase")]
+pub struct SlowDown {
+    #[builder(default = stringify_ty!(SlowDown))]
+    pub code: &'static str,
+
+    #[builder(default = "Please reduce your request rate.")]
+    pub message: &'static str,
+
+    pub resource: Option<String>,
+
+    pub request_id: Option<String>,
+}
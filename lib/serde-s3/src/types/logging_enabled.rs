@@ -0,0 +1,11 @@
+use bon::Builder;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Builder, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LoggingEnabled {
+    pub target_bucket: String,
+
+    pub target_prefix: String,
+}
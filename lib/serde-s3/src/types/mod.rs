@@ -5,6 +5,7 @@ mod bucket;
 mod bucket_canned_acl;
 mod bucket_info;
 mod bucket_location_constraint;
+mod bucket_logging_status;
 mod bucket_type;
 mod bucket_versioning_status;
 mod checksum_algorithm;
@@ -20,6 +21,7 @@ mod encoding_type;
 mod initiator;
 mod location_info;
 mod location_type;
+mod logging_enabled;
 mod mfa_delete_status;
 mod multipart_upload;
 mod object;
@@ -49,6 +51,7 @@ pub use bucket::Bucket;
 pub use bucket_canned_acl::BucketCannedAcl;
 pub use bucket_info::BucketInfo;
 pub use bucket_location_constraint::BucketLocationConstraint;
+pub use bucket_logging_status::BucketLoggingStatus;
 pub use bucket_type::BucketType;
 pub use bucket_versioning_status::BucketVersioningStatus;
 pub use checksum_algorithm::ChecksumAlgorithm;
@@ -64,6 +67,7 @@ pub use encoding_type::EncodingType;
 pub use initiator::Initiator;
 pub use location_info::LocationInfo;
 pub use location_type::LocationType;
+pub use logging_enabled::LoggingEnabled;
 pub use mfa_delete_status::MfaDeleteStatus;
 pub use multipart_upload::MultipartUpload;
 pub use object::Object;
@@ -1,8 +1,11 @@
 use serde::Deserialize;
 use serde::Serialize;
+use strum::EnumString;
+use strum::IntoStaticStr;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, EnumString, IntoStaticStr)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum ObjectStorageClass {
     DeepArchive,
     ExpressOnezone,
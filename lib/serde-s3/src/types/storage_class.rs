@@ -1,8 +1,11 @@
 use serde::Deserialize;
 use serde::Serialize;
+use strum::EnumString;
+use strum::IntoStaticStr;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumString, IntoStaticStr)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum StorageClass {
     DeepArchive,
     ExpressOnezone,
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+use crate::types::AccessLogFormat;
+use crate::types::LogStream;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLogConfig {
+    pub stream: LogStream,
+
+    pub format: AccessLogFormat,
+}
@@ -0,0 +1,12 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct AdminConfig {
+    pub token: Option<String>,
+
+    pub socket: Option<SocketAddr>,
+}
@@ -0,0 +1,13 @@
+use bytesize::ByteSize;
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[default(ByteSize::mib(32))]
+    pub size: ByteSize,
+
+    #[default = 60]
+    pub stats_interval: u64,
+}
@@ -0,0 +1,16 @@
+use bytesize::ByteSize;
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    #[default = true]
+    pub enabled: bool,
+
+    pub objects: bool,
+
+    #[default(ByteSize::kib(1))]
+    pub min_size: ByteSize,
+}
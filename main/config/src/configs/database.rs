@@ -1,3 +1,4 @@
+use bytesize::ByteSize;
 use serde::Deserialize;
 use serde::Serialize;
 use smart_default::SmartDefault;
@@ -37,9 +38,21 @@ pub struct DatabaseConfig {
 
     #[default = 1]
     pub slow_threshold: u64,
+
+    pub chunk_size: Option<ByteSize>,
+
+    #[default(ByteSize::kib(4))]
+    pub inline_threshold: ByteSize,
+
+    #[default = 3600]
+    pub staging_ttl: u64,
 }
 
 impl DatabaseConfig {
+    pub fn chunk_size(&self) -> ByteSize {
+        self.chunk_size.unwrap_or_else(|| self.driver.chunk_size())
+    }
+
     pub fn try_to_url(&self) -> UrlParseResult {
         Ok(match &self.url {
             Some(url) => url.clone(),
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitConfig {
+    pub ip: RateLimit,
+
+    pub access_key: RateLimit,
+
+    pub owner: RateLimit,
+
+    pub bucket: RateLimit,
+}
+
+#[derive(Debug, Clone, Copy, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    pub rate: Option<f64>,
+
+    pub burst: Option<u32>,
+
+    pub concurrency: Option<usize>,
+}
+
+impl RateLimit {
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.rate.is_some() || self.concurrency.is_some()
+    }
+
+    pub fn burst(&self) -> f64 {
+        self.burst
+            .map(f64::from)
+            .or(self.rate)
+            .unwrap_or_default()
+            .max(1.0)
+    }
+}
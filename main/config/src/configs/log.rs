@@ -2,6 +2,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use smart_default::SmartDefault;
 
+use crate::configs::AccessLogConfig;
 use crate::types::LogFormat;
 use crate::types::LogLevel;
 use crate::types::LogStream;
@@ -17,4 +18,11 @@ pub struct LogConfig {
     #[cfg_attr(debug_assertions, default(LogFormat::Pretty))]
     #[cfg_attr(not(debug_assertions), default(LogFormat::Compact))]
     pub format: LogFormat,
+
+    pub otlp: Option<String>,
+
+    pub access: Option<AccessLogConfig>,
+
+    #[default = 300]
+    pub delivery_interval: u64,
 }
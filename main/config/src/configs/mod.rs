@@ -1,9 +1,28 @@
+mod access_log;
+mod admin;
 mod app;
+mod cache;
+mod compression;
 mod database;
+mod limit;
 mod log;
+mod mount;
+mod scrub;
 mod server;
+mod tier;
+mod tls;
 
+pub use access_log::AccessLogConfig;
+pub use admin::AdminConfig;
 pub use app::AppConfig;
+pub use cache::CacheConfig;
+pub use compression::CompressionConfig;
 pub use database::DatabaseConfig;
+pub use limit::LimitConfig;
+pub use limit::RateLimit;
 pub use log::LogConfig;
+pub use mount::MountConfig;
+pub use scrub::ScrubConfig;
 pub use server::ServerConfig;
+pub use tier::TierConfig;
+pub use tls::TlsConfig;
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct MountConfig {
+    pub bucket: Option<String>,
+
+    pub path: Option<PathBuf>,
+
+    #[default = 60]
+    pub interval: u64,
+}
@@ -0,0 +1,18 @@
+use bytesize::ByteSize;
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct ScrubConfig {
+    #[default = 86400]
+    pub interval: u64,
+
+    #[default(Some(ByteSize::mib(16)))]
+    pub rate: Option<ByteSize>,
+
+    #[default = 100]
+    pub batch_size: u64,
+
+    pub reject: bool,
+}
@@ -1,11 +1,16 @@
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
 use smart_default::SmartDefault;
 
+use crate::configs::CompressionConfig;
+use crate::configs::TlsConfig;
+
 #[derive(Debug, SmartDefault, Serialize, Deserialize)]
 pub struct ServerConfig {
     #[cfg_attr(debug_assertions, default(Ipv4Addr::LOCALHOST.into()))]
@@ -17,6 +22,19 @@ pub struct ServerConfig {
     pub port: u16,
 
     pub socket: Option<SocketAddr>,
+
+    #[serde(default, deserialize_with = "deserialize_sockets")]
+    pub sockets: Vec<SocketAddr>,
+
+    pub unix: Option<PathBuf>,
+
+    pub domain: Option<String>,
+
+    pub drain: u64,
+
+    pub tls: TlsConfig,
+
+    pub compression: CompressionConfig,
 }
 
 impl ServerConfig {
@@ -24,4 +42,37 @@ impl ServerConfig {
         self.socket
             .unwrap_or_else(|| SocketAddr::new(self.host, self.port))
     }
+
+    pub fn to_sockets(&self) -> Vec<SocketAddr> {
+        let mut sockets = vec![self.to_socket()];
+        for socket in &self.sockets {
+            if !sockets.contains(socket) {
+                sockets.push(*socket);
+            }
+        }
+
+        sockets
+    }
+}
+
+fn deserialize_sockets<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<SocketAddr>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Sockets {
+        List(Vec<SocketAddr>),
+        String(String),
+    }
+
+    match Sockets::deserialize(deserializer)? {
+        Sockets::List(sockets) => Ok(sockets),
+        Sockets::String(sockets) => sockets
+            .split(',')
+            .map(str::trim)
+            .filter(|socket| !socket.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(serde::de::Error::custom),
+    }
 }
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use bytesize::ByteSize;
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct TierConfig {
+    pub path: Option<PathBuf>,
+
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+
+    #[default = 4]
+    pub data_shards: usize,
+
+    #[default = 2]
+    pub parity_shards: usize,
+
+    #[default = 300]
+    pub interval: u64,
+
+    #[default = 100]
+    pub batch_size: u64,
+
+    pub min_age: Option<u64>,
+
+    #[default(Some(ByteSize::mib(1)))]
+    pub min_size: Option<ByteSize>,
+
+    #[default = true]
+    pub noncurrent: bool,
+
+    #[default(vec![
+        "STANDARD_IA".to_owned(),
+        "ONEZONE_IA".to_owned(),
+        "GLACIER".to_owned(),
+        "GLACIER_IR".to_owned(),
+        "DEEP_ARCHIVE".to_owned(),
+    ])]
+    pub storage_classes: Vec<String>,
+}
@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use smart_default::SmartDefault;
+
+#[derive(Debug, SmartDefault, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub cert: Option<PathBuf>,
+
+    pub key: Option<PathBuf>,
+
+    pub ca: Option<PathBuf>,
+
+    #[default = 60]
+    pub interval: u64,
+}
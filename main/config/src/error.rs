@@ -1,3 +1,5 @@
+use std::fmt;
+
 use derive_more::Display;
 use derive_more::Error;
 use derive_more::From;
@@ -14,3 +16,15 @@ pub enum UrlParseError {
     Username,
     Password,
 }
+
+#[derive(Debug, Error)]
+pub struct ValidateError {
+    #[error(not(source))]
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.errors.join("; "))
+    }
+}
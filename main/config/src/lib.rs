@@ -4,3 +4,6 @@ mod types;
 mod utils;
 
 pub use configs::AppConfig;
+pub use configs::RateLimit;
+pub use error::ValidateError;
+pub use types::AccessLogFormat;
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::fmt::Display;
 
+use bytesize::ByteSize;
 use fmt::Formatter;
 use serde::Deserialize;
 use serde::Serialize;
@@ -14,6 +15,16 @@ pub enum DatabaseDriver {
     Mysql,
 }
 
+impl DatabaseDriver {
+    pub fn chunk_size(&self) -> ByteSize {
+        match self {
+            Self::Sqlite => ByteSize::mib(1),
+            Self::Postgres => ByteSize::mib(5),
+            Self::Mysql => ByteSize::mib(4),
+        }
+    }
+}
+
 impl Display for DatabaseDriver {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
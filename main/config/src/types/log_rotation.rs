@@ -0,0 +1,12 @@
+use bytesize::ByteSize;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Size(ByteSize),
+}
@@ -1,9 +1,16 @@
 use std::io;
+use std::path::Path;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use serde::Serialize;
 use tracing_appender::non_blocking::NonBlocking;
 use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_appender::rolling::Rotation;
+
+use crate::types::LogRotation;
+use crate::utils::SizeRollingFile;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -11,13 +18,51 @@ pub enum LogStream {
     #[default]
     StdOut,
     StdErr,
+    File {
+        path: PathBuf,
+
+        #[serde(default)]
+        rotation: LogRotation,
+
+        retention: Option<usize>,
+    },
 }
 
 impl LogStream {
-    pub fn to_writer(&self) -> (NonBlocking, WorkerGuard) {
+    pub fn to_writer(&self) -> io::Result<(NonBlocking, WorkerGuard)> {
         match self {
-            Self::StdOut => tracing_appender::non_blocking(io::stdout()),
-            Self::StdErr => tracing_appender::non_blocking(io::stderr()),
+            Self::StdOut => Ok(tracing_appender::non_blocking(io::stdout())),
+            Self::StdErr => Ok(tracing_appender::non_blocking(io::stderr())),
+            Self::File {
+                path,
+                rotation,
+                retention,
+            } => match rotation {
+                LogRotation::Hourly => rolling_appender(path, Rotation::HOURLY, *retention),
+                LogRotation::Daily => rolling_appender(path, Rotation::DAILY, *retention),
+                LogRotation::Size(size) => Ok(tracing_appender::non_blocking(
+                    SizeRollingFile::new(path.clone(), size.as_u64(), *retention)?,
+                )),
+            },
         }
     }
 }
+
+fn rolling_appender(
+    path: &Path,
+    rotation: Rotation,
+    retention: Option<usize>,
+) -> io::Result<(NonBlocking, WorkerGuard)> {
+    let mut builder = RollingFileAppender::builder().rotation(rotation);
+    if let Some(prefix) = path.file_name().and_then(|name| name.to_str()) {
+        builder = builder.filename_prefix(prefix);
+    }
+    if let Some(retention) = retention {
+        builder = builder.max_log_files(retention);
+    }
+    let appender = builder
+        .build(path.parent().unwrap_or_else(|| Path::new(".")))
+        .map_err(io::Error::other)?;
+
+    Ok(tracing_appender::non_blocking(appender))
+}
@@ -1,9 +1,13 @@
+pub mod access_log_format;
 mod database_driver;
 pub mod log_format;
 pub mod log_level;
+pub mod log_rotation;
 pub mod log_stream;
 
+pub use access_log_format::AccessLogFormat;
 pub use database_driver::DatabaseDriver;
 pub use log_format::LogFormat;
 pub use log_level::LogLevel;
+pub use log_rotation::LogRotation;
 pub use log_stream::LogStream;
@@ -1,3 +1,5 @@
 mod format_behavior;
+mod size_rolling_file;
 
 pub use format_behavior::FormatBehavior;
+pub use size_rolling_file::SizeRollingFile;
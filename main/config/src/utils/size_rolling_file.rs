@@ -0,0 +1,174 @@
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct SizeRollingFile {
+    path: PathBuf,
+    limit: u64,
+    retention: Option<usize>,
+    file: File,
+    size: u64,
+}
+
+impl SizeRollingFile {
+    pub(crate) fn new(path: PathBuf, limit: u64, retention: Option<usize>) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            limit,
+            retention,
+            file,
+            size,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let mut count = 0;
+        while self.rotated_path(count + 1).exists() {
+            count += 1;
+        }
+        let keep = self
+            .retention
+            .map_or(usize::MAX, |retention| retention.saturating_sub(1));
+        for index in keep.max(1)..=count {
+            fs::remove_file(self.rotated_path(index))?;
+        }
+        for index in (1..=count.min(keep.saturating_sub(1))).rev() {
+            fs::rename(self.rotated_path(index), self.rotated_path(index + 1))?;
+        }
+        if keep > 0 {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.limit {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+
+    fn write_rotated(name: &str, retention: Option<usize>) -> Vec<Option<String>> {
+        let dir = env::temp_dir().join(format!("minil-rolling-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut file = SizeRollingFile::new(dir.join("app.log"), 4, retention).unwrap();
+        for line in ["aaaa", "bbbb", "cccc", "dddd"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let contents = (0..5)
+            .map(|index| {
+                let path = match index {
+                    0 => file.path.clone(),
+                    index => file.rotated_path(index),
+                };
+                fs::read_to_string(path).ok()
+            })
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        contents
+    }
+
+    #[test]
+    fn test_rotate_unlimited() {
+        assert_eq!(
+            write_rotated("unlimited", None),
+            [
+                Some("dddd".to_owned()),
+                Some("cccc".to_owned()),
+                Some("bbbb".to_owned()),
+                Some("aaaa".to_owned()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rotate_single() {
+        assert_eq!(
+            write_rotated("single", Some(1)),
+            [Some("dddd".to_owned()), None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn test_rotate_retention() {
+        assert_eq!(
+            write_rotated("retention", Some(3)),
+            [
+                Some("dddd".to_owned()),
+                Some("cccc".to_owned()),
+                Some("bbbb".to_owned()),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_appends() {
+        let dir = env::temp_dir().join(format!("minil-rolling-{}-appends", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join("app.log");
+        SizeRollingFile::new(path.clone(), 4, None)
+            .unwrap()
+            .write_all(b"aa")
+            .unwrap();
+        let mut file = SizeRollingFile::new(path.clone(), 4, None).unwrap();
+        file.write_all(b"bbb").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bbb");
+        assert_eq!(fs::read_to_string(file.rotated_path(1)).unwrap(), "aa");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -18,6 +18,18 @@ pub struct Model {
 
     pub versioning: Option<bool>,
 
+    pub quota_size: Option<i64>,
+
+    pub quota_objects: Option<i64>,
+
+    pub usage_size: i64,
+
+    pub usage_objects: i64,
+
+    pub logging_target: Option<String>,
+
+    pub logging_prefix: Option<String>,
+
     #[sea_orm(default_expr = "Expr::current_timestamp()")]
     pub created_at: DateTimeUtc,
 
@@ -29,6 +41,15 @@ impl Model {
     pub fn last_modified(&self) -> DateTimeUtc {
         self.updated_at.unwrap_or(self.created_at)
     }
+
+    #[must_use]
+    pub fn exceeds_quota(&self, size: i64, objects: i64) -> bool {
+        self.quota_size
+            .is_some_and(|quota_size| size > 0 && self.usage_size.saturating_add(size) > quota_size)
+            || self.quota_objects.is_some_and(|quota_objects| {
+                objects > 0 && self.usage_objects.saturating_add(objects) > quota_objects
+            })
+    }
 }
 
 #[derive(Debug, Clone, EnumIter, DeriveRelation)]
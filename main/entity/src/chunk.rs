@@ -1,6 +1,7 @@
 use sea_orm::entity::prelude::*;
 
 use super::prelude::*;
+use super::sea_orm_active_enums::ChunkTier;
 
 #[derive(Debug, Clone, DeriveEntityModel)]
 #[sea_orm(table_name = "chunk")]
@@ -14,6 +15,9 @@ pub struct Model {
     #[sea_orm(indexed, unique)]
     pub version_part_id: Option<Uuid>,
 
+    #[sea_orm(indexed)]
+    pub staging_id: Option<Uuid>,
+
     #[sea_orm(indexed, unique)]
     pub index: i64,
 
@@ -21,12 +25,16 @@ pub struct Model {
 
     pub end: i64,
 
-    pub data: Vec<u8>,
+    pub tier: ChunkTier,
+
+    pub data: Option<Vec<u8>>,
 
     #[sea_orm(default_expr = "Expr::current_timestamp()")]
     pub created_at: DateTimeUtc,
 
     pub updated_at: Option<DateTimeUtc>,
+
+    pub degraded_at: Option<DateTimeUtc>,
 }
 
 impl Model {
@@ -51,6 +59,13 @@ pub enum Relation {
         to = "super::version_part::Column::Id"
     )]
     VersionPart,
+
+    #[sea_orm(
+        belongs_to = "Staging",
+        from = "Column::StagingId",
+        to = "super::staging::Column::Id"
+    )]
+    Staging,
 }
 
 impl Related<UploadPart> for Entity {
@@ -65,4 +80,10 @@ impl Related<VersionPart> for Entity {
     }
 }
 
+impl Related<Staging> for Entity {
+    fn to() -> RelationDef {
+        Relation::Staging.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "corrupt_version")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    #[sea_orm(indexed, unique)]
+    pub version_id: Uuid,
+
+    pub version_part_id: Option<Uuid>,
+
+    pub reason: String,
+
+    #[sea_orm(default_expr = "Expr::current_timestamp()")]
+    pub created_at: DateTimeUtc,
+
+    pub updated_at: Option<DateTimeUtc>,
+}
+
+impl Model {
+    #[must_use]
+    pub fn last_modified(&self) -> DateTimeUtc {
+        self.updated_at.unwrap_or(self.created_at)
+    }
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "Version",
+        from = "Column::VersionId",
+        to = "super::version::Column::Id"
+    )]
+    Version,
+}
+
+impl Related<Version> for Entity {
+    fn to() -> RelationDef {
+        Relation::Version.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -2,8 +2,11 @@ pub mod prelude;
 
 pub mod bucket;
 pub mod chunk;
+pub mod corrupt_version;
 pub mod object;
 pub mod owner;
+pub mod sea_orm_active_enums;
+pub mod staging;
 pub mod tag;
 pub mod tag_set;
 pub mod upload;
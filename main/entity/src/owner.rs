@@ -11,8 +11,36 @@ pub struct Model {
     #[sea_orm(indexed, unique)]
     pub name: String,
 
+    pub quota_size: Option<i64>,
+
+    pub quota_objects: Option<i64>,
+
+    pub usage_size: i64,
+
+    pub usage_objects: i64,
+
+    #[sea_orm(unique)]
+    pub access_key: Option<String>,
+
+    pub secret_key: Option<String>,
+
+    pub disabled: bool,
+
     #[sea_orm(default_expr = "Expr::current_timestamp()")]
     pub created_at: DateTimeUtc,
+
+    pub updated_at: Option<DateTimeUtc>,
+}
+
+impl Model {
+    #[must_use]
+    pub fn exceeds_quota(&self, size: i64, objects: i64) -> bool {
+        self.quota_size
+            .is_some_and(|quota_size| size > 0 && self.usage_size.saturating_add(size) > quota_size)
+            || self.quota_objects.is_some_and(|quota_objects| {
+                objects > 0 && self.usage_objects.saturating_add(objects) > quota_objects
+            })
+    }
 }
 
 #[derive(Debug, Clone, EnumIter, DeriveRelation)]
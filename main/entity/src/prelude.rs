@@ -1,7 +1,9 @@
 pub use super::bucket::Entity as Bucket;
 pub use super::chunk::Entity as Chunk;
+pub use super::corrupt_version::Entity as CorruptVersion;
 pub use super::object::Entity as Object;
 pub use super::owner::Entity as Owner;
+pub use super::staging::Entity as Staging;
 pub use super::tag::Entity as Tag;
 pub use super::tag_set::Entity as TagSet;
 pub use super::upload::Entity as Upload;
@@ -0,0 +1,14 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum ChunkTier {
+    #[sea_orm(string_value = "database")]
+    Database,
+
+    #[sea_orm(string_value = "filesystem")]
+    Filesystem,
+
+    #[sea_orm(string_value = "erasure")]
+    Erasure,
+}
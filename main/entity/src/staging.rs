@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "staging")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    #[sea_orm(default_expr = "Expr::current_timestamp()")]
+    pub created_at: DateTimeUtc,
+
+    pub updated_at: Option<DateTimeUtc>,
+}
+
+impl Model {
+    #[must_use]
+    pub fn last_modified(&self) -> DateTimeUtc {
+        self.updated_at.unwrap_or(self.created_at)
+    }
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "Chunk")]
+    Chunk,
+}
+
+impl Related<Chunk> for Entity {
+    fn to() -> RelationDef {
+        Relation::Chunk.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
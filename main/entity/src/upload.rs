@@ -16,6 +16,8 @@ pub struct Model {
 
     pub mime: Option<String>,
 
+    pub storage_class: Option<String>,
+
     #[sea_orm(default_expr = "Expr::current_timestamp()")]
     pub created_at: DateTimeUtc,
 }
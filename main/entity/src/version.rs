@@ -17,6 +17,8 @@ pub struct Model {
 
     pub mime: Option<String>,
 
+    pub storage_class: Option<String>,
+
     pub size: Option<i64>,
 
     #[sea_orm(column_type = "Binary(4)")]
@@ -39,6 +41,10 @@ pub struct Model {
 
     pub e_tag: Option<String>,
 
+    pub data: Option<Vec<u8>>,
+
+    pub path: Option<String>,
+
     #[sea_orm(default_expr = "Expr::current_timestamp()")]
     pub created_at: DateTimeUtc,
 
@@ -93,6 +99,9 @@ pub enum Relation {
 
     #[sea_orm(has_one = "TagSet")]
     TagSet,
+
+    #[sea_orm(has_one = "CorruptVersion")]
+    CorruptVersion,
 }
 
 impl Related<Object> for Entity {
@@ -113,4 +122,10 @@ impl Related<TagSet> for Entity {
     }
 }
 
+impl Related<CorruptVersion> for Entity {
+    fn to() -> RelationDef {
+        Relation::CorruptVersion.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,5 @@
+ALTER TABLE chunk
+    DROP CONSTRAINT chunk_check;
+
+ALTER TABLE chunk
+    ADD CONSTRAINT chunk_check CHECK ((CASE WHEN upload_part_id IS NOT NULL THEN 1 ELSE 0 END) + (CASE WHEN version_part_id IS NOT NULL THEN 1 ELSE 0 END) + (CASE WHEN staging_id IS NOT NULL THEN 1 ELSE 0 END) = 1);
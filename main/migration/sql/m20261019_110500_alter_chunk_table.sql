@@ -0,0 +1,2 @@
+ALTER TABLE chunk
+    ADD CONSTRAINT chunk_tier_check CHECK ((tier = 'database') = (data IS NOT NULL));
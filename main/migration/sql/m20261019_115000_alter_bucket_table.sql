@@ -0,0 +1,72 @@
+CREATE FUNCTION update_usage(usage_object_id uuid, delta_size bigint, delta_objects bigint) RETURNS void AS $$
+    WITH bucket_usage AS (
+        UPDATE bucket
+        SET usage_size = bucket.usage_size + delta_size, usage_objects = bucket.usage_objects + delta_objects
+        FROM object
+        WHERE object.id = usage_object_id AND bucket.id = object.bucket_id
+        RETURNING bucket.owner_id
+    )
+    UPDATE owner
+    SET usage_size = owner.usage_size + delta_size, usage_objects = owner.usage_objects + delta_objects
+    FROM bucket_usage
+    WHERE owner.id = bucket_usage.owner_id;
+$$ LANGUAGE sql;
+
+CREATE FUNCTION version_usage() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.parts_count IS NOT NULL THEN
+        PERFORM update_usage(OLD.object_id, -COALESCE(OLD.size, 0), -1);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.parts_count IS NOT NULL THEN
+        PERFORM update_usage(NEW.object_id, COALESCE(NEW.size, 0), 1);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER trg_version_usage AFTER INSERT OR UPDATE OR DELETE ON version
+    DEFERRABLE INITIALLY DEFERRED FOR EACH ROW EXECUTE FUNCTION version_usage();
+
+CREATE FUNCTION object_usage() RETURNS trigger AS $$
+BEGIN
+    PERFORM update_usage(OLD.id, -COALESCE(SUM(size), 0)::bigint, -COUNT(*))
+    FROM version
+    WHERE object_id = OLD.id AND parts_count IS NOT NULL;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_object_usage BEFORE DELETE ON object
+    FOR EACH ROW EXECUTE FUNCTION object_usage();
+
+CREATE FUNCTION bucket_usage() RETURNS trigger AS $$
+BEGIN
+    UPDATE owner
+    SET usage_size = usage_size - OLD.usage_size, usage_objects = usage_objects - OLD.usage_objects
+    WHERE id = OLD.owner_id;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_bucket_usage BEFORE DELETE ON bucket
+    FOR EACH ROW EXECUTE FUNCTION bucket_usage();
+
+UPDATE bucket
+SET usage_size = usage.size, usage_objects = usage.objects
+FROM (
+    SELECT object.bucket_id, COALESCE(SUM(version.size), 0) AS size, COUNT(*) AS objects
+    FROM version
+    JOIN object ON object.id = version.object_id
+    WHERE version.parts_count IS NOT NULL
+    GROUP BY object.bucket_id
+) AS usage
+WHERE bucket.id = usage.bucket_id;
+
+UPDATE owner
+SET usage_size = usage.size, usage_objects = usage.objects
+FROM (
+    SELECT owner_id, SUM(usage_size) AS size, SUM(usage_objects) AS objects
+    FROM bucket
+    GROUP BY owner_id
+) AS usage
+WHERE owner.id = usage.owner_id;
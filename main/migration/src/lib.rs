@@ -10,6 +10,21 @@ mod m20250714_045800_create_version_part_table;
 mod m20250717_051402_create_chunk_table;
 mod m20250802_161509_create_tag_set_table;
 mod m20250802_162159_create_tag_table;
+mod m20261019_101500_alter_chunk_table;
+mod m20261019_103000_alter_version_table;
+mod m20261019_110000_alter_version_table;
+mod m20261019_110500_alter_chunk_table;
+mod m20261019_113000_create_corrupt_version_table;
+mod m20261019_114000_alter_version_table;
+mod m20261019_114500_alter_owner_table;
+mod m20261019_115000_alter_bucket_table;
+mod m20261019_120000_alter_owner_table;
+mod m20261019_121500_alter_owner_table;
+mod m20261019_123000_alter_bucket_table;
+mod m20261019_124500_create_staging_table;
+mod m20261019_125000_alter_upload_table;
+mod m20261019_125500_alter_chunk_table;
+mod m20261019_130000_alter_owner_table;
 
 pub struct Migrator;
 
@@ -27,6 +42,21 @@ impl MigratorTrait for Migrator {
             Box::new(m20250717_051402_create_chunk_table::Migration),
             Box::new(m20250802_161509_create_tag_set_table::Migration),
             Box::new(m20250802_162159_create_tag_table::Migration),
+            Box::new(m20261019_101500_alter_chunk_table::Migration),
+            Box::new(m20261019_103000_alter_version_table::Migration),
+            Box::new(m20261019_110000_alter_version_table::Migration),
+            Box::new(m20261019_110500_alter_chunk_table::Migration),
+            Box::new(m20261019_113000_create_corrupt_version_table::Migration),
+            Box::new(m20261019_114000_alter_version_table::Migration),
+            Box::new(m20261019_114500_alter_owner_table::Migration),
+            Box::new(m20261019_115000_alter_bucket_table::Migration),
+            Box::new(m20261019_120000_alter_owner_table::Migration),
+            Box::new(m20261019_121500_alter_owner_table::Migration),
+            Box::new(m20261019_123000_alter_bucket_table::Migration),
+            Box::new(m20261019_124500_create_staging_table::Migration),
+            Box::new(m20261019_125000_alter_upload_table::Migration),
+            Box::new(m20261019_125500_alter_chunk_table::Migration),
+            Box::new(m20261019_130000_alter_owner_table::Migration),
         ]
     }
 }
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chunk::Table)
+                    .add_column(uuid_null(Chunk::StagingId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chunk_staging_id")
+                    .table(Chunk::Table)
+                    .col(Chunk::StagingId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(include_str!(
+                "../sql/m20261019_101500_alter_chunk_table.sql"
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Chunk::Table)
+                    .and_where(Expr::col(Chunk::StagingId).is_not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE chunk DROP CONSTRAINT chunk_check; \
+                 ALTER TABLE chunk ADD CONSTRAINT chunk_check CHECK ((CASE WHEN upload_part_id IS NOT NULL THEN 1 ELSE 0 END) + (CASE WHEN version_part_id IS NOT NULL THEN 1 ELSE 0 END) = 1);",
+            )
+            .await?;
+
+        manager
+            .drop_index(Index::drop().name("idx_chunk_staging_id").to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chunk::Table)
+                    .drop_column(Chunk::StagingId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chunk {
+    Table,
+    StagingId,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chunk::Table)
+                    .add_column(string(Chunk::Tier).default("database"))
+                    .modify_column(binary_null(Chunk::Data))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chunk_tier")
+                    .table(Chunk::Table)
+                    .col(Chunk::Tier)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(include_str!(
+                "../sql/m20261019_110500_alter_chunk_table.sql"
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE chunk DROP CONSTRAINT chunk_tier_check;")
+            .await?;
+
+        manager
+            .drop_index(Index::drop().name("idx_chunk_tier").to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chunk::Table)
+                    .drop_column(Chunk::Tier)
+                    .modify_column(binary(Chunk::Data))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chunk {
+    Table,
+    Tier,
+    Data,
+}
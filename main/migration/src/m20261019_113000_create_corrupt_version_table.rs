@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CorruptVersion::Table)
+                    .col(pk_uuid(CorruptVersion::Id))
+                    .col(uuid_uniq(CorruptVersion::VersionId))
+                    .col(uuid_null(CorruptVersion::VersionPartId))
+                    .col(string(CorruptVersion::Reason))
+                    .col(
+                        timestamp_with_time_zone(CorruptVersion::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(CorruptVersion::UpdatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_corrupt_version_version")
+                            .from(CorruptVersion::Table, CorruptVersion::VersionId)
+                            .to(Version::Table, Version::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_corrupt_version_version_part")
+                            .from(CorruptVersion::Table, CorruptVersion::VersionPartId)
+                            .to(VersionPart::Table, VersionPart::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_corrupt_version_version_id")
+                    .table(CorruptVersion::Table)
+                    .col(CorruptVersion::VersionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_corrupt_version_version_id")
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(CorruptVersion::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CorruptVersion {
+    Table,
+    Id,
+    VersionId,
+    VersionPartId,
+    Reason,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Version {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum VersionPart {
+    Table,
+    Id,
+}
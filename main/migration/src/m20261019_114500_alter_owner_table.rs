@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Owner::Table)
+                    .add_column(big_integer_null(Owner::QuotaSize))
+                    .add_column(big_integer_null(Owner::QuotaObjects))
+                    .add_column(big_integer(Owner::UsageSize).default(0))
+                    .add_column(big_integer(Owner::UsageObjects).default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Owner::Table)
+                    .drop_column(Owner::QuotaSize)
+                    .drop_column(Owner::QuotaObjects)
+                    .drop_column(Owner::UsageSize)
+                    .drop_column(Owner::UsageObjects)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Owner {
+    Table,
+    QuotaSize,
+    QuotaObjects,
+    UsageSize,
+    UsageObjects,
+}
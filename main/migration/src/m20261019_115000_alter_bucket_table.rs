@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bucket::Table)
+                    .add_column(big_integer_null(Bucket::QuotaSize))
+                    .add_column(big_integer_null(Bucket::QuotaObjects))
+                    .add_column(big_integer(Bucket::UsageSize).default(0))
+                    .add_column(big_integer(Bucket::UsageObjects).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(include_str!(
+                "../sql/m20261019_115000_alter_bucket_table.sql"
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DROP TRIGGER trg_bucket_usage ON bucket;
+                DROP TRIGGER trg_object_usage ON object;
+                DROP TRIGGER trg_version_usage ON version;
+                DROP FUNCTION bucket_usage();
+                DROP FUNCTION object_usage();
+                DROP FUNCTION version_usage();
+                DROP FUNCTION update_usage(uuid, bigint, bigint);",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bucket::Table)
+                    .drop_column(Bucket::QuotaSize)
+                    .drop_column(Bucket::QuotaObjects)
+                    .drop_column(Bucket::UsageSize)
+                    .drop_column(Bucket::UsageObjects)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Bucket {
+    Table,
+    QuotaSize,
+    QuotaObjects,
+    UsageSize,
+    UsageObjects,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Staging::Table)
+                    .col(pk_uuid(Staging::Id))
+                    .col(
+                        timestamp_with_time_zone(Staging::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(Staging::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Staging::Table)
+                    .columns([Staging::Id])
+                    .select_from(
+                        Query::select()
+                            .distinct()
+                            .column(Chunk::StagingId)
+                            .from(Chunk::Table)
+                            .and_where(Expr::col(Chunk::StagingId).is_not_null())
+                            .to_owned(),
+                    )
+                    .map_err(|err| DbErr::Migration(err.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_chunk_staging")
+                    .from(Chunk::Table, Chunk::StagingId)
+                    .to(Staging::Table, Staging::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_chunk_staging")
+                    .table(Chunk::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Staging::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Staging {
+    Table,
+    Id,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Chunk {
+    Table,
+    StagingId,
+}
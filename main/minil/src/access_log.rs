@@ -0,0 +1,488 @@
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::mem;
+use std::sync::Arc;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::extract::FromRequestParts;
+use axum::extract::RawPathParams;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::HeaderName;
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use http_body_util::BodyExt as _;
+use minil_config::AccessLogFormat;
+use minil_config::AppConfig;
+use minil_entity::owner;
+use minil_service::prelude::*;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use sea_orm::DbConn;
+use sea_orm::TransactionTrait;
+use sea_orm::prelude::DateTimeUtc;
+use serde::Serialize;
+use tower_http::BoxError;
+use tracing::error;
+use tracing::warn;
+use tracing_appender::non_blocking::NonBlocking;
+use uuid::Uuid;
+
+use crate::NODE_ID_HEADER;
+use crate::REQUEST_ID_HEADER;
+use crate::VERSION_ID_HEADER;
+use crate::error::AppErrorDiscriminants;
+use crate::metrics::Operation;
+use crate::tls::ClientOwner;
+
+const MAX_DELIVERY_RECORDS: usize = 100_000;
+
+#[derive(Clone)]
+pub(crate) struct AccessLog {
+    stream: Option<(NonBlocking, AccessLogFormat)>,
+    delivery: LogDelivery,
+}
+
+impl AccessLog {
+    pub(crate) fn new(
+        stream: Option<(NonBlocking, AccessLogFormat)>,
+        delivery: LogDelivery,
+    ) -> Self {
+        Self { stream, delivery }
+    }
+}
+
+// logging buckets are refreshed on every delivery, so configuration changes apply from the next one
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LogDelivery {
+    buffer: Arc<Mutex<DeliveryBuffer>>,
+    buckets: Arc<RwLock<HashSet<(String, String)>>>,
+}
+
+#[derive(Debug, Default)]
+struct DeliveryBuffer {
+    records: Vec<DeliveryRecord>,
+    dropped: usize,
+}
+
+#[derive(Debug)]
+struct DeliveryRecord {
+    owner: String,
+    bucket: String,
+    line: String,
+}
+
+impl LogDelivery {
+    fn is_logging(&self, owner: &str, bucket: &str) -> bool {
+        self.buckets
+            .read()
+            .contains(&(owner.to_owned(), bucket.to_owned()))
+    }
+
+    fn set_buckets(&self, buckets: HashSet<(String, String)>) {
+        *self.buckets.write() = buckets;
+    }
+
+    fn push(&self, record: DeliveryRecord) {
+        let mut buffer = self.buffer.lock();
+        if buffer.records.len() < MAX_DELIVERY_RECORDS {
+            buffer.records.push(record);
+        } else {
+            buffer.dropped += 1;
+        }
+    }
+
+    fn take(&self) -> (Vec<DeliveryRecord>, usize) {
+        let buffer = mem::take(&mut *self.buffer.lock());
+
+        (buffer.records, buffer.dropped)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct AccessRecord {
+    time: DateTimeUtc,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bucket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    method: String,
+    uri: String,
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_received: Option<u64>,
+    bytes_sent: u64,
+    duration_ms: u128,
+    turn_around_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    referer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_agent: Option<String>,
+}
+
+impl AccessRecord {
+    fn to_s3_line(&self) -> String {
+        let dash = |value: Option<&str>| value.unwrap_or("-").to_owned();
+        let quote = |value: Option<&str>| format!("\"{}\"", value.unwrap_or("-"));
+
+        let mut line = String::new();
+        let _ = writeln!(
+            line,
+            "{owner} {bucket} [{time}] {remote_ip} {owner} {request_id} {operation} {key} \
+             \"{method} {uri} HTTP/1.1\" {status} {error} {bytes_sent} - {duration} {turn_around} \
+             {referer} {user_agent} {version_id} {host_id} - - - {host} - - -",
+            owner = dash(self.owner.as_deref()),
+            bucket = dash(self.bucket.as_deref()),
+            time = self.time.format("%d/%b/%Y:%H:%M:%S %z"),
+            remote_ip = dash(self.remote_ip.as_deref()),
+            request_id = dash(self.request_id.as_deref()),
+            operation = self.operation.as_ref().map_or_else(
+                || "-".to_owned(),
+                |operation| format!("REST.{}.{operation}", self.method)
+            ),
+            key = dash(self.key.as_deref()),
+            method = self.method,
+            uri = self.uri,
+            status = self.status,
+            error = dash(self.error.as_deref()),
+            bytes_sent = match self.bytes_sent {
+                0 => "-".to_owned(),
+                bytes => bytes.to_string(),
+            },
+            duration = self.duration_ms,
+            turn_around = self.turn_around_ms,
+            referer = quote(self.referer.as_deref()),
+            user_agent = quote(self.user_agent.as_deref()),
+            version_id = dash(self.version_id.as_deref()),
+            host_id = dash(self.host_id.as_deref()),
+            host = dash(self.host.as_deref()),
+        );
+
+        line
+    }
+
+    fn to_json_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("failed to serialize access record");
+        line.push('\n');
+
+        line
+    }
+}
+
+struct PendingRecord {
+    access_log: AccessLog,
+    record: AccessRecord,
+    start_time: Instant,
+}
+
+impl PendingRecord {
+    fn add_bytes_sent(&mut self, len: usize) {
+        self.record.bytes_sent += len as u64;
+    }
+}
+
+impl Drop for PendingRecord {
+    fn drop(&mut self) {
+        self.record.duration_ms = self.start_time.elapsed().as_millis();
+
+        if let Some((writer, format)) = &mut self.access_log.stream {
+            let line = match format {
+                AccessLogFormat::S3 => self.record.to_s3_line(),
+                AccessLogFormat::Json => self.record.to_json_line(),
+            };
+            if let Err(err) = writer.write_all(line.as_bytes()) {
+                warn!(%err, "failed to write access log");
+            }
+        }
+        if let (Some(owner), Some(bucket)) = (&self.record.owner, &self.record.bucket)
+            && self.access_log.delivery.is_logging(owner, bucket)
+        {
+            self.access_log.delivery.push(DeliveryRecord {
+                owner: owner.clone(),
+                bucket: bucket.clone(),
+                line: self.record.to_s3_line(),
+            });
+        }
+    }
+}
+
+pub(crate) async fn record_access(
+    State(access_log): State<AccessLog>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start_time = Instant::now();
+    let mut record = AccessRecord {
+        time: SystemTime::now().into(),
+        ..Default::default()
+    };
+
+    let (mut parts, body) = request.into_parts();
+    if let Ok(params) = RawPathParams::from_request_parts(&mut parts, &()).await {
+        for (name, value) in &params {
+            match name {
+                "Bucket" => record.bucket = Some(value.to_owned()),
+                "Key" => record.key = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+    }
+    record.remote_ip = parts
+        .extensions
+        .get::<ConnectInfo<ClientOwner>>()
+        .map(|ConnectInfo(client_owner)| client_owner.addr.ip().to_string());
+    record.method = parts.method.to_string();
+    record.uri = parts.uri.to_string();
+    record.request_id = find_header(&parts.headers, &REQUEST_ID_HEADER);
+    record.bytes_received =
+        find_header(&parts.headers, &header::CONTENT_LENGTH).and_then(|size| size.parse().ok());
+    record.host_id = find_header(&parts.headers, &NODE_ID_HEADER);
+    record.host = find_header(&parts.headers, &header::HOST);
+    record.referer = find_header(&parts.headers, &header::REFERER);
+    record.user_agent = find_header(&parts.headers, &header::USER_AGENT);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    record.turn_around_ms = start_time.elapsed().as_millis();
+    record.status = response.status().as_u16();
+    record.owner = response
+        .extensions()
+        .get::<owner::Model>()
+        .map(|owner| owner.name.clone());
+    record.operation = response
+        .extensions()
+        .get::<Operation>()
+        .map(|Operation(operation)| operation.to_string());
+    record.error = response
+        .extensions()
+        .get::<AppErrorDiscriminants>()
+        .map(ToString::to_string);
+    record.version_id = find_header(response.headers(), &VERSION_ID_HEADER);
+
+    let mut pending = PendingRecord {
+        access_log,
+        record,
+        start_time,
+    };
+    response.map(|body| {
+        Body::new(body.map_frame(move |frame| {
+            if let Some(data) = frame.data_ref() {
+                pending.add_bytes_sent(data.len());
+            }
+            frame
+        }))
+    })
+}
+
+fn find_header(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
+pub(crate) async fn deliver(
+    db_conn: &DbConn,
+    config: &AppConfig,
+    delivery: &LogDelivery,
+) -> Result<usize, BoxError> {
+    let buckets = BucketQuery::find_many_logging_also_owner(db_conn).await?;
+    delivery.set_buckets(
+        buckets
+            .into_iter()
+            .filter_map(|(bucket, owner)| Some((owner?.name, bucket.name)))
+            .collect(),
+    );
+
+    let (records, dropped) = delivery.take();
+    if dropped > 0 {
+        warn!(
+            dropped,
+            limit = MAX_DELIVERY_RECORDS,
+            "dropped access log records"
+        );
+    }
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for record in records {
+        groups
+            .entry((record.owner, record.bucket))
+            .or_default()
+            .push(record.line);
+    }
+
+    let mut delivered = 0;
+    for ((owner, bucket), lines) in groups {
+        match deliver_lines(db_conn, config, &owner, &bucket, &lines).await {
+            Ok(true) => delivered += 1,
+            Ok(false) => {}
+            Err(err) => {
+                error!(owner, bucket, %err, "DeliveryError");
+                for line in lines {
+                    delivery.push(DeliveryRecord {
+                        owner: owner.clone(),
+                        bucket: bucket.clone(),
+                        line,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(delivered)
+}
+
+async fn deliver_lines(
+    db_conn: &DbConn,
+    config: &AppConfig,
+    owner: &str,
+    bucket: &str,
+    lines: &[String],
+) -> Result<bool, BoxError> {
+    let Some(owner) = OwnerQuery::find(db_conn, owner).await? else {
+        return Ok(false);
+    };
+    let Some(bucket) = BucketQuery::find(db_conn, owner.id, bucket).await? else {
+        return Ok(false);
+    };
+    let (Some(target), Some(prefix)) = (bucket.logging_target, bucket.logging_prefix) else {
+        return Ok(false);
+    };
+    let Some(target) = BucketQuery::find(db_conn, owner.id, &target).await? else {
+        warn!(
+            bucket = bucket.name,
+            target, "missing logging target bucket"
+        );
+        return Ok(false);
+    };
+
+    let unique = Uuid::new_v4().simple().to_string().to_uppercase();
+    let key = format!(
+        "{prefix}{}-{}",
+        DateTimeUtc::from(SystemTime::now()).format("%Y-%m-%d-%H-%M-%S"),
+        &unique[..16],
+    );
+    let db_txn = db_conn.begin().await?;
+    ObjectMutation::upsert_also_version(
+        &db_txn,
+        db_conn,
+        target.id,
+        key,
+        target.versioning.unwrap_or_default(),
+        Some(&mime::TEXT_PLAIN),
+        None,
+        config.database.chunk_size(),
+        config.database.inline_threshold,
+        lines.concat().as_bytes(),
+    )
+    .await?;
+    db_txn.commit().await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_record() -> AccessRecord {
+        AccessRecord {
+            time: "2026-10-19T09:53:01Z".parse().unwrap(),
+            request_id: Some("request-id".to_owned()),
+            remote_ip: Some("127.0.0.1".to_owned()),
+            owner: Some("minil".to_owned()),
+            operation: Some("GetObject".to_owned()),
+            bucket: Some("bkt".to_owned()),
+            key: Some("dir/key".to_owned()),
+            method: "GET".to_owned(),
+            uri: "/bkt/dir/key".to_owned(),
+            status: 200,
+            bytes_sent: 5,
+            duration_ms: 7,
+            turn_around_ms: 3,
+            user_agent: Some("curl".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_s3_line() {
+        assert_eq!(
+            find_record().to_s3_line(),
+            "minil bkt [19/Oct/2026:09:53:01 +0000] 127.0.0.1 minil request-id REST.GET.GetObject \
+             dir/key \"GET /bkt/dir/key HTTP/1.1\" 200 - 5 - 7 3 \"-\" \"curl\" - - - - - - - - \
+             -\n"
+        );
+
+        let record = AccessRecord {
+            method: "PUT".to_owned(),
+            uri: "/".to_owned(),
+            status: 403,
+            error: Some("AccessDenied".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            record.to_s3_line(),
+            "- - [01/Jan/1970:00:00:00 +0000] - - - - - \"PUT / HTTP/1.1\" 403 AccessDenied - - \
+             0 0 \"-\" \"-\" - - - - - - - - -\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json_line() {
+        let line = find_record().to_json_line();
+        assert!(line.ends_with('\n'));
+
+        let value = serde_json::from_str::<serde_json::Value>(&line).unwrap();
+        assert_eq!(value["operation"], "GetObject");
+        assert_eq!(value["bytes_sent"], 5);
+        assert!(value.get("error").is_none());
+    }
+
+    #[test]
+    fn test_log_delivery() {
+        let delivery = LogDelivery::default();
+        assert!(!delivery.is_logging("minil", "bkt"));
+
+        delivery.set_buckets(HashSet::from([("minil".to_owned(), "bkt".to_owned())]));
+        assert!(delivery.is_logging("minil", "bkt"));
+        assert!(!delivery.is_logging("other", "bkt"));
+
+        for _ in 0..=MAX_DELIVERY_RECORDS {
+            delivery.push(DeliveryRecord {
+                owner: "minil".to_owned(),
+                bucket: "bkt".to_owned(),
+                line: String::new(),
+            });
+        }
+        let (records, dropped) = delivery.take();
+        assert_eq!(records.len(), MAX_DELIVERY_RECORDS);
+        assert_eq!(dropped, 1);
+
+        let (records, dropped) = delivery.take();
+        assert!(records.is_empty());
+        assert_eq!(dropped, 0);
+    }
+}
@@ -0,0 +1,584 @@
+use std::num::TryFromIntError;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::Path;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::http::header;
+use axum::middleware;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::routing::get;
+use axum::routing::post;
+use axum_extra::extract::Query;
+use futures::TryStreamExt;
+use minil_config::AppConfig;
+use minil_entity::bucket;
+use minil_entity::owner;
+use minil_entity::upload;
+use minil_service::ChunkCache;
+use minil_service::ChunkStorage;
+use minil_service::prelude::*;
+use prometheus::TEXT_FORMAT;
+use sea_orm::DbConn;
+use sea_orm::DbErr;
+use sea_orm::prelude::DateTimeUtc;
+use serde::Deserialize;
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+use tracing::error;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::metrics;
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+struct CorruptVersion {
+    bucket: String,
+    key: String,
+    version_id: Uuid,
+    version_part_id: Option<Uuid>,
+    reason: String,
+    last_modified: DateTimeUtc,
+}
+
+#[derive(Debug, Serialize)]
+struct Quota {
+    size: Option<i64>,
+    objects: Option<i64>,
+    usage_size: i64,
+    usage_objects: i64,
+}
+
+impl From<bucket::Model> for Quota {
+    fn from(bucket: bucket::Model) -> Self {
+        Self {
+            size: bucket.quota_size,
+            objects: bucket.quota_objects,
+            usage_size: bucket.usage_size,
+            usage_objects: bucket.usage_objects,
+        }
+    }
+}
+
+impl From<owner::Model> for Quota {
+    fn from(owner: owner::Model) -> Self {
+        Self {
+            size: owner.quota_size,
+            objects: owner.quota_objects,
+            usage_size: owner.usage_size,
+            usage_objects: owner.usage_objects,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Owner {
+    name: String,
+    access_key: Option<String>,
+    disabled: bool,
+    quota: Quota,
+    created_at: DateTimeUtc,
+}
+
+impl From<owner::Model> for Owner {
+    fn from(owner: owner::Model) -> Self {
+        Self {
+            name: owner.name.clone(),
+            access_key: owner.access_key.clone(),
+            disabled: owner.disabled,
+            created_at: owner.created_at,
+            quota: owner.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OwnerKeys {
+    name: String,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+}
+
+impl From<owner::Model> for OwnerKeys {
+    fn from(owner: owner::Model) -> Self {
+        Self {
+            name: owner.name,
+            access_key: owner.access_key,
+            secret_key: owner.secret_key,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PostOwner {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatchOwner {
+    disabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Bucket {
+    owner: String,
+    name: String,
+    versioning: Option<bool>,
+    quota: Quota,
+    created_at: DateTimeUtc,
+}
+
+#[derive(Debug, Serialize)]
+struct Upload {
+    id: Uuid,
+    key: String,
+    created_at: DateTimeUtc,
+}
+
+impl From<upload::Model> for Upload {
+    fn from(upload: upload::Model) -> Self {
+        Self {
+            id: upload.id,
+            key: upload.key,
+            created_at: upload.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OwnerFilter {
+    owner: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadFilter {
+    owner: Option<String>,
+    key: Option<String>,
+    before: Option<DateTimeUtc>,
+}
+
+#[derive(Debug, Serialize)]
+struct AbortedUploads {
+    aborted: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Garbage {
+    staged: u64,
+    orphaned: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PutQuota {
+    size: Option<u64>,
+    objects: Option<u64>,
+}
+
+impl PutQuota {
+    fn into_limits(self) -> Result<(Option<i64>, Option<i64>), TryFromIntError> {
+        Ok((
+            self.size.map(i64::try_from).transpose()?,
+            self.objects.map(i64::try_from).transpose()?,
+        ))
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn internal_error(err: DbErr) -> StatusCode {
+    error!(%err, "DatabaseError");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+async fn find_bucket(
+    db_conn: &DbConn,
+    owner: Option<&str>,
+    bucket: &str,
+) -> Result<bucket::Model, StatusCode> {
+    let owner = OwnerQuery::find(db_conn, owner.unwrap_or("minil"))
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    BucketQuery::find(db_conn, owner.id, bucket)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+pub(crate) fn router(state: AppState, token: String) -> Router {
+    Router::new()
+        .route("/corrupt-versions", get(list_corrupt_versions))
+        .route("/owners", get(list_owners).post(create_owner))
+        .route("/owners/{owner}", get(get_owner).patch(update_owner))
+        .route(
+            "/owners/{owner}/keys",
+            post(rotate_owner_keys).delete(revoke_owner_keys),
+        )
+        .route("/buckets", get(list_buckets))
+        .route(
+            "/buckets/{bucket}/uploads",
+            get(list_uploads).delete(abort_uploads),
+        )
+        .route("/jobs/gc", post(run_gc))
+        .route("/jobs/scrub", post(run_scrub))
+        .route(
+            "/buckets/{bucket}/quota",
+            get(get_bucket_quota).put(put_bucket_quota),
+        )
+        .route(
+            "/owners/{owner}/quota",
+            get(get_owner_quota).put(put_owner_quota),
+        )
+        .route("/metrics", get(get_metrics))
+        .route_layer(middleware::from_fn_with_state(token, validate_token))
+        .with_state(state)
+}
+
+async fn validate_token(
+    State(token): State<String>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if bearer.is_some_and(|bearer| bool::from(bearer.as_bytes().ct_eq(token.as_bytes()))) {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+#[instrument(skip(db_conn))]
+async fn list_corrupt_versions(
+    State(db_conn): State<DbConn>,
+) -> Result<Json<Vec<CorruptVersion>>, StatusCode> {
+    let corrupt_versions = CorruptVersionQuery::find_many_also_object(&db_conn)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(
+        corrupt_versions
+            .into_iter()
+            .map(
+                |(bucket, key, version_id, version_part_id, reason, last_modified)| {
+                    CorruptVersion {
+                        bucket,
+                        key,
+                        version_id,
+                        version_part_id,
+                        reason,
+                        last_modified,
+                    }
+                },
+            )
+            .collect(),
+    ))
+}
+
+#[instrument(skip(db_conn, chunk_cache))]
+async fn get_metrics(
+    State(db_conn): State<DbConn>,
+    State(chunk_cache): State<Arc<ChunkCache>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let metrics = metrics::encode(&db_conn, &chunk_cache)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(([(header::CONTENT_TYPE, TEXT_FORMAT)], metrics))
+}
+
+#[instrument(skip(db_conn))]
+async fn get_bucket_quota(
+    State(db_conn): State<DbConn>,
+    Path(bucket): Path<String>,
+    Query(filter): Query<OwnerFilter>,
+) -> Result<Json<Quota>, StatusCode> {
+    let bucket = find_bucket(&db_conn, filter.owner.as_deref(), &bucket).await?;
+
+    Ok(Json(bucket.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn put_bucket_quota(
+    State(db_conn): State<DbConn>,
+    Path(bucket): Path<String>,
+    Query(filter): Query<OwnerFilter>,
+    Json(quota): Json<PutQuota>,
+) -> Result<Json<Quota>, StatusCode> {
+    let (size, objects) = quota.into_limits().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let bucket = find_bucket(&db_conn, filter.owner.as_deref(), &bucket).await?;
+    let bucket =
+        BucketMutation::update_quota(&db_conn, bucket.owner_id, &bucket.name, size, objects)
+            .await
+            .map_err(internal_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(bucket.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn get_owner_quota(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+) -> Result<Json<Quota>, StatusCode> {
+    let owner = OwnerQuery::find(&db_conn, &owner)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(owner.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn put_owner_quota(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+    Json(quota): Json<PutQuota>,
+) -> Result<Json<Quota>, StatusCode> {
+    let (size, objects) = quota.into_limits().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let owner = OwnerMutation::update_quota(&db_conn, &owner, size, objects)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(owner.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn list_owners(State(db_conn): State<DbConn>) -> Result<Json<Vec<Owner>>, StatusCode> {
+    let owners = OwnerQuery::find_many(&db_conn)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(owners.into_iter().map(Into::into).collect()))
+}
+
+#[instrument(skip(db_conn))]
+async fn create_owner(
+    State(db_conn): State<DbConn>,
+    Json(owner): Json<PostOwner>,
+) -> Result<(StatusCode, Json<OwnerKeys>), StatusCode> {
+    let owner = OwnerMutation::insert(&db_conn, owner.name)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::CONFLICT)?;
+
+    Ok((StatusCode::CREATED, Json(owner.into())))
+}
+
+#[instrument(skip(db_conn))]
+async fn get_owner(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+) -> Result<Json<Owner>, StatusCode> {
+    let owner = OwnerQuery::find(&db_conn, &owner)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(owner.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn update_owner(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+    Json(patch): Json<PatchOwner>,
+) -> Result<Json<Owner>, StatusCode> {
+    let owner = OwnerMutation::update_disabled(&db_conn, &owner, patch.disabled)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(owner.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn rotate_owner_keys(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+) -> Result<Json<OwnerKeys>, StatusCode> {
+    let owner = OwnerMutation::update_keys(&db_conn, &owner)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(owner.into()))
+}
+
+#[instrument(skip(db_conn))]
+async fn revoke_owner_keys(
+    State(db_conn): State<DbConn>,
+    Path(owner): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    OwnerMutation::delete_keys(&db_conn, &owner)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[instrument(skip(db_conn))]
+async fn list_buckets(
+    State(db_conn): State<DbConn>,
+    Query(filter): Query<OwnerFilter>,
+) -> Result<Json<Vec<Bucket>>, StatusCode> {
+    let buckets = BucketQuery::find_all_also_owner(&db_conn)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(
+        buckets
+            .into_iter()
+            .map(|(bucket, owner)| Bucket {
+                owner: owner.map(|owner| owner.name).unwrap_or_default(),
+                name: bucket.name.clone(),
+                versioning: bucket.versioning,
+                created_at: bucket.created_at,
+                quota: bucket.into(),
+            })
+            .filter(|bucket| {
+                filter
+                    .owner
+                    .as_ref()
+                    .is_none_or(|owner| *owner == bucket.owner)
+            })
+            .collect(),
+    ))
+}
+
+#[instrument(skip(db_conn))]
+async fn list_uploads(
+    State(db_conn): State<DbConn>,
+    Path(bucket): Path<String>,
+    Query(filter): Query<OwnerFilter>,
+) -> Result<Json<Vec<Upload>>, StatusCode> {
+    let bucket = find_bucket(&db_conn, filter.owner.as_deref(), &bucket).await?;
+    let uploads = UploadQuery::find_many(&db_conn, bucket.id, None, None, None, None)
+        .await
+        .map_err(internal_error)?
+        .map_ok(Into::into)
+        .try_collect()
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(uploads))
+}
+
+#[instrument(skip(db_conn))]
+async fn abort_uploads(
+    State(db_conn): State<DbConn>,
+    Path(bucket): Path<String>,
+    Query(filter): Query<UploadFilter>,
+) -> Result<Json<AbortedUploads>, StatusCode> {
+    let bucket = find_bucket(&db_conn, filter.owner.as_deref(), &bucket).await?;
+    let res =
+        UploadMutation::delete_many(&db_conn, bucket.id, filter.key.as_deref(), filter.before)
+            .await
+            .map_err(internal_error)?;
+
+    Ok(Json(AbortedUploads {
+        aborted: res.rows_affected,
+    }))
+}
+
+#[instrument(skip(db_conn, chunk_storage, config))]
+async fn run_gc(
+    State(db_conn): State<DbConn>,
+    State(chunk_storage): State<Arc<ChunkStorage>>,
+    State(config): State<Arc<AppConfig>>,
+) -> Result<Json<Garbage>, StatusCode> {
+    let before = SystemTime::now() - Duration::from_secs(config.database.staging_ttl);
+    let (staged, orphaned) = crate::collect_garbage(&db_conn, &chunk_storage, before)
+        .await
+        .map_err(|err| {
+            error!(%err, "GcError");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(Garbage { staged, orphaned }))
+}
+
+#[instrument(skip(db_conn, chunk_storage, config, scrub_lock))]
+async fn run_scrub(
+    State(db_conn): State<DbConn>,
+    State(chunk_storage): State<Arc<ChunkStorage>>,
+    State(config): State<Arc<AppConfig>>,
+    State(scrub_lock): State<Arc<Mutex<()>>>,
+) -> StatusCode {
+    let Ok(guard) = scrub_lock.try_lock_owned() else {
+        return StatusCode::CONFLICT;
+    };
+    tokio::spawn(async move {
+        crate::scrub_all_versions(&db_conn, &chunk_storage, &config).await;
+        drop(guard);
+    });
+
+    StatusCode::ACCEPTED
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_token() {
+        let router = Router::new()
+            .route("/metrics", get(async || StatusCode::OK))
+            .route_layer(middleware::from_fn_with_state(
+                "token".to_owned(),
+                validate_token,
+            ));
+        let status = async |authorization: Option<&str>| {
+            let mut request = Request::get("/metrics");
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+            let request = request.body(Body::empty()).unwrap();
+            router.clone().oneshot(request).await.unwrap().status()
+        };
+
+        assert_eq!(status(Some("Bearer token")).await, StatusCode::OK);
+        assert_eq!(status(Some("Bearer toke")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(Some("Bearer tokens")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(Some("token")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_into_limits() {
+        let quota = PutQuota {
+            size: Some(1024),
+            objects: None,
+        };
+        assert_eq!(quota.into_limits(), Ok((Some(1024), None)));
+
+        let quota = PutQuota {
+            size: None,
+            objects: Some(u64::MAX),
+        };
+        assert!(quota.into_limits().is_err());
+    }
+}
@@ -0,0 +1,608 @@
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::pin;
+use std::sync::Arc;
+
+use bytesize::ByteSize;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use minil_config::AppConfig;
+use minil_entity::version;
+use minil_entity::version_part;
+use minil_service::ChunkCache;
+use minil_service::ChunkStorage;
+use minil_service::prelude::*;
+use sea_orm::DbConn;
+use sea_orm::TransactionTrait;
+use sea_orm::prelude::DateTimeUtc;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::fs;
+use tokio::fs::File;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::BufWriter;
+use tokio_tar::Archive;
+use tokio_tar::Builder;
+use tokio_tar::Entries;
+use tokio_tar::Header;
+use tokio_util::io::StreamReader;
+use tower_http::BoxError;
+use tracing::info;
+use uuid::Uuid;
+
+const MANIFEST: &str = "manifest.ndjson";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Bucket(BucketRecord),
+    Version(Box<VersionRecord>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BucketRecord {
+    name: String,
+    mfa_delete: Option<bool>,
+    versioning: Option<bool>,
+    tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionRecord {
+    key: String,
+    id: Uuid,
+    latest: bool,
+    versioning: bool,
+    parts_count: Option<i16>,
+    mime: Option<String>,
+    storage_class: Option<String>,
+    size: Option<i64>,
+    crc32: Option<String>,
+    crc32_c: Option<String>,
+    crc64_nvme: Option<String>,
+    sha1: Option<String>,
+    sha256: Option<String>,
+    md5: Option<String>,
+    e_tag: Option<String>,
+    inline: bool,
+    created_at: DateTimeUtc,
+    updated_at: Option<DateTimeUtc>,
+    tags: Vec<(String, String)>,
+    parts: Vec<PartRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PartRecord {
+    number: i16,
+    size: i64,
+    crc32: String,
+    crc32_c: String,
+    crc64_nvme: String,
+    sha1: String,
+    sha256: String,
+    md5: String,
+}
+
+impl VersionRecord {
+    fn new(
+        key: String,
+        version: &version::Model,
+        latest: bool,
+        tags: Vec<(String, String)>,
+        parts: Vec<PartRecord>,
+    ) -> Self {
+        Self {
+            key,
+            id: version.id,
+            latest,
+            versioning: version.versioning,
+            parts_count: version.parts_count,
+            mime: version.mime.clone(),
+            storage_class: version.storage_class.clone(),
+            size: version.size,
+            crc32: version.crc32.as_ref().map(hex::encode),
+            crc32_c: version.crc32_c.as_ref().map(hex::encode),
+            crc64_nvme: version.crc64_nvme.as_ref().map(hex::encode),
+            sha1: version.sha1.as_ref().map(hex::encode),
+            sha256: version.sha256.as_ref().map(hex::encode),
+            md5: version.md5.as_ref().map(hex::encode),
+            e_tag: version.e_tag.clone(),
+            inline: version.data.is_some(),
+            created_at: version.created_at,
+            updated_at: version.updated_at,
+            tags,
+            parts,
+        }
+    }
+
+    fn to_model(&self, data: Option<Vec<u8>>) -> Result<version::Model, hex::FromHexError> {
+        Ok(version::Model {
+            id: self.id,
+            object_id: Uuid::nil(),
+            versioning: self.versioning,
+            parts_count: self.parts_count,
+            mime: self.mime.clone(),
+            storage_class: self.storage_class.clone(),
+            size: self.size,
+            crc32: self.crc32.as_ref().map(hex::decode).transpose()?,
+            crc32_c: self.crc32_c.as_ref().map(hex::decode).transpose()?,
+            crc64_nvme: self.crc64_nvme.as_ref().map(hex::decode).transpose()?,
+            sha1: self.sha1.as_ref().map(hex::decode).transpose()?,
+            sha256: self.sha256.as_ref().map(hex::decode).transpose()?,
+            md5: self.md5.as_ref().map(hex::decode).transpose()?,
+            e_tag: self.e_tag.clone(),
+            data,
+            path: None,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl From<&version_part::Model> for PartRecord {
+    fn from(part: &version_part::Model) -> Self {
+        Self {
+            number: part.number,
+            size: part.size,
+            crc32: hex::encode(&part.crc32),
+            crc32_c: hex::encode(&part.crc32_c),
+            crc64_nvme: hex::encode(&part.crc64_nvme),
+            sha1: hex::encode(&part.sha1),
+            sha256: hex::encode(&part.sha256),
+            md5: hex::encode(&part.md5),
+        }
+    }
+}
+
+impl From<&version::Model> for PartRecord {
+    fn from(version: &version::Model) -> Self {
+        Self {
+            number: 1,
+            size: version.size.unwrap_or_default(),
+            crc32: version.crc32.as_ref().map(hex::encode).unwrap_or_default(),
+            crc32_c: version
+                .crc32_c
+                .as_ref()
+                .map(hex::encode)
+                .unwrap_or_default(),
+            crc64_nvme: version
+                .crc64_nvme
+                .as_ref()
+                .map(hex::encode)
+                .unwrap_or_default(),
+            sha1: version.sha1.as_ref().map(hex::encode).unwrap_or_default(),
+            sha256: version.sha256.as_ref().map(hex::encode).unwrap_or_default(),
+            md5: version.md5.as_ref().map(hex::encode).unwrap_or_default(),
+        }
+    }
+}
+
+impl PartRecord {
+    fn to_model(&self, version_id: Uuid) -> Result<version_part::Model, hex::FromHexError> {
+        Ok(version_part::Model {
+            id: Uuid::nil(),
+            version_id,
+            number: self.number,
+            start: 0,
+            end: 0,
+            size: self.size,
+            crc32: hex::decode(&self.crc32)?,
+            crc32_c: hex::decode(&self.crc32_c)?,
+            crc64_nvme: hex::decode(&self.crc64_nvme)?,
+            sha1: hex::decode(&self.sha1)?,
+            sha256: hex::decode(&self.sha256)?,
+            md5: hex::decode(&self.md5)?,
+            created_at: DateTimeUtc::default(),
+        })
+    }
+}
+
+fn data_path(version_id: Uuid, number: i16) -> String {
+    format!("data/{version_id}/{number}")
+}
+
+enum ArchiveWriter {
+    Tar(Builder<File>),
+    Dir(PathBuf),
+}
+
+impl ArchiveWriter {
+    async fn create(path: &Path, dir: bool) -> io::Result<Self> {
+        Ok(if dir {
+            fs::create_dir_all(path).await?;
+            Self::Dir(path.to_owned())
+        } else {
+            Self::Tar(Builder::new(File::create(path).await?))
+        })
+    }
+
+    async fn append(
+        &mut self,
+        name: &str,
+        size: u64,
+        mtime: DateTimeUtc,
+        mut read: impl AsyncRead + Unpin,
+    ) -> io::Result<()> {
+        match self {
+            Self::Tar(builder) => {
+                let mut header = Header::new_gnu();
+                header.set_size(size);
+                header.set_mode(0o644);
+                header.set_mtime(mtime.timestamp().max(0) as u64);
+                header.set_cksum();
+                builder.append_data(&mut header, name, read).await
+            }
+            Self::Dir(root) => {
+                let path = root.join(name);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+
+                let mut file = File::create(path).await?;
+                tokio::io::copy(&mut read, &mut file).await?;
+                file.sync_all().await
+            }
+        }
+    }
+
+    async fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Tar(builder) => builder.into_inner().await?.shutdown().await,
+            Self::Dir(_) => Ok(()),
+        }
+    }
+}
+
+enum ArchiveReader {
+    Tar(Box<Entries<File>>),
+    Dir(PathBuf),
+}
+
+impl ArchiveReader {
+    async fn open(path: &Path) -> io::Result<Self> {
+        Ok(if fs::metadata(path).await?.is_dir() {
+            Self::Dir(path.to_owned())
+        } else {
+            Self::Tar(Box::new(Archive::new(File::open(path).await?).entries()?))
+        })
+    }
+
+    async fn next(&mut self, name: &str) -> io::Result<Box<dyn AsyncRead + Unpin + Send + '_>> {
+        match self {
+            Self::Tar(entries) => {
+                let entry = entries
+                    .next()
+                    .await
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))??;
+                if entry.path()? != Path::new(name) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("expected {name}, found {}", entry.path()?.display()),
+                    ));
+                }
+
+                Ok(Box::new(entry))
+            }
+            Self::Dir(root) => Ok(Box::new(File::open(root.join(name)).await?)),
+        }
+    }
+}
+
+async fn find_tags(
+    db_conn: &DbConn,
+    tag_set_id: Option<Uuid>,
+) -> Result<Vec<(String, String)>, BoxError> {
+    let Some(tag_set_id) = tag_set_id else {
+        return Ok(vec![]);
+    };
+
+    Ok(TagQuery::find_many(db_conn, tag_set_id)
+        .await?
+        .map_ok(|tag| (tag.key, tag.value))
+        .try_collect()
+        .await?)
+}
+
+async fn write_record(
+    write: &mut (impl AsyncWrite + Unpin),
+    record: &Record,
+) -> Result<(), BoxError> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    write.write_all(&line).await?;
+
+    Ok(())
+}
+
+async fn write_manifest(
+    db_conn: &DbConn,
+    owner_id: Uuid,
+    bucket: &str,
+    versions: bool,
+    path: &Path,
+) -> Result<String, BoxError> {
+    let (bucket, tag_set) = BucketQuery::find_also_tag_set(db_conn, owner_id, bucket)
+        .await?
+        .ok_or("no such bucket")?;
+
+    let mut manifest = BufWriter::new(File::create(path).await?);
+    let record = Record::Bucket(BucketRecord {
+        name: bucket.name.clone(),
+        mfa_delete: bucket.mfa_delete,
+        versioning: bucket.versioning,
+        tags: find_tags(db_conn, tag_set.map(|tag_set| tag_set.id)).await?,
+    });
+    write_record(&mut manifest, &record).await?;
+
+    let object_versions = if versions {
+        VersionQuery::find_many_both_object(db_conn, bucket.id, None, None, None, None)
+            .await?
+            .map_ok(|(version, object)| {
+                let latest = object.version_id == version.id;
+                (object.key, version, latest)
+            })
+            .left_stream()
+    } else {
+        ObjectQuery::find_many_both_latest_version(db_conn, bucket.id, None, None, None)
+            .await?
+            .map_ok(|(object, version)| (object.key, version, true))
+            .right_stream()
+    };
+    let mut object_versions = pin!(object_versions);
+    while let Some((key, version, latest)) = object_versions.try_next().await? {
+        let tag_set = TagSetQuery::find(db_conn, None, None, Some(version.id)).await?;
+        let tags = find_tags(db_conn, tag_set.map(|tag_set| tag_set.id)).await?;
+        let part_records = if version.path.is_some() {
+            vec![PartRecord::from(&version)]
+        } else {
+            VersionPartQuery::find_many_ranged(db_conn, version.id, None)
+                .await?
+                .map_ok(|part| PartRecord::from(&part))
+                .try_collect()
+                .await?
+        };
+
+        let record = VersionRecord::new(key, &version, latest, tags, part_records);
+        write_record(&mut manifest, &Record::Version(Box::new(record))).await?;
+    }
+    manifest.flush().await?;
+    manifest.into_inner().sync_all().await?;
+
+    Ok(bucket.name)
+}
+
+async fn write_data(
+    db_conn: &DbConn,
+    chunk_storage: &Arc<ChunkStorage>,
+    writer: &mut ArchiveWriter,
+    manifest: &Path,
+) -> Result<u64, BoxError> {
+    let chunk_cache = Arc::new(ChunkCache::new(ByteSize::b(0)));
+
+    let mut count = 0;
+    let mut lines = BufReader::new(File::open(manifest).await?).lines();
+    while let Some(line) = lines.next_line().await? {
+        let Record::Version(record) = serde_json::from_str(&line)? else {
+            continue;
+        };
+        let version = VersionQuery::find(db_conn, record.id)
+            .await?
+            .filter(|version| {
+                version.last_modified() == record.updated_at.unwrap_or(record.created_at)
+            })
+            .ok_or_else(|| format!("version {} changed during export", record.id))?;
+
+        let mtime = version.last_modified();
+        if let Some(data) = &version.data {
+            writer
+                .append(
+                    &data_path(version.id, 0),
+                    data.len() as u64,
+                    mtime,
+                    data.as_slice(),
+                )
+                .await?;
+        }
+
+        if let Some(path) = &version.path {
+            writer
+                .append(
+                    &data_path(version.id, 1),
+                    version.size(),
+                    mtime,
+                    File::open(path).await?.take(version.size()),
+                )
+                .await?;
+        } else {
+            let parts: Vec<version_part::Model> =
+                VersionPartQuery::find_many_ranged(db_conn, version.id, None)
+                    .await?
+                    .try_collect()
+                    .await?;
+            for part in parts {
+                let stream = ChunkQuery::find_many_ranged_part_data_by_version_part_id(
+                    db_conn.clone(),
+                    Arc::clone(&chunk_cache),
+                    Arc::clone(chunk_storage),
+                    version.id,
+                    part.id,
+                    None,
+                )
+                .map_err(io::Error::other);
+                writer
+                    .append(
+                        &data_path(version.id, part.number),
+                        part.size as u64,
+                        mtime,
+                        StreamReader::new(Box::pin(stream)),
+                    )
+                    .await?;
+            }
+        }
+
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+pub(crate) async fn export(
+    db_conn: &DbConn,
+    chunk_storage: Arc<ChunkStorage>,
+    owner: &str,
+    bucket: &str,
+    path: &Path,
+    versions: bool,
+    dir: bool,
+) -> Result<(), BoxError> {
+    let owner = OwnerQuery::find(db_conn, owner)
+        .await?
+        .ok_or("no such owner")?;
+
+    // the manifest leads the archive, so it is staged on disk before any data is written
+    let mut manifest = path.as_os_str().to_owned();
+    manifest.push(".manifest.tmp");
+    let manifest = PathBuf::from(manifest);
+
+    let res = async {
+        let bucket = write_manifest(db_conn, owner.id, bucket, versions, &manifest).await?;
+
+        let mut writer = ArchiveWriter::create(path, dir).await?;
+        writer
+            .append(
+                MANIFEST,
+                fs::metadata(&manifest).await?.len(),
+                DateTimeUtc::default(),
+                File::open(&manifest).await?,
+            )
+            .await?;
+        let count = write_data(db_conn, &chunk_storage, &mut writer, &manifest).await?;
+        writer.finish().await?;
+
+        Ok::<_, BoxError>((bucket, count))
+    }
+    .await;
+    fs::remove_file(&manifest).await.ok();
+    let (bucket, count) = res?;
+
+    info!(bucket, versions = count, "exported bucket");
+
+    Ok(())
+}
+
+pub(crate) async fn import(
+    db_conn: &DbConn,
+    config: &AppConfig,
+    owner: &str,
+    path: &Path,
+    bucket: Option<String>,
+) -> Result<(), BoxError> {
+    let mut reader = ArchiveReader::open(path).await?;
+
+    let mut manifest = vec![];
+    reader
+        .next(MANIFEST)
+        .await?
+        .read_to_end(&mut manifest)
+        .await?;
+    let mut records = manifest
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_slice::<Record>);
+
+    let Some(Record::Bucket(record)) = records.next().transpose()? else {
+        return Err("missing bucket record".into());
+    };
+    let name = bucket.unwrap_or(record.name);
+
+    let owner = OwnerQuery::find(db_conn, owner)
+        .await?
+        .ok_or("no such owner")?;
+    let db_txn = db_conn.begin().await?;
+    let bucket = BucketMutation::insert(&db_txn, owner.id, name.clone())
+        .await?
+        .ok_or("failed to create bucket")?;
+    BucketMutation::update_versioning(
+        &db_txn,
+        owner.id,
+        &name,
+        record.mfa_delete,
+        record.versioning,
+    )
+    .await?;
+    if !record.tags.is_empty() {
+        TagSetMutation::upsert_with_tag(
+            &db_txn,
+            Some(bucket.id),
+            None,
+            None,
+            record.tags.into_iter(),
+        )
+        .await?;
+    }
+    db_txn.commit().await?;
+
+    let mut count = 0;
+    for record in records {
+        let Record::Version(record) = record? else {
+            return Err("unexpected bucket record".into());
+        };
+
+        let data = if record.inline {
+            let mut data = vec![];
+            reader
+                .next(&data_path(record.id, 0))
+                .await?
+                .read_to_end(&mut data)
+                .await?;
+
+            Some(data)
+        } else {
+            None
+        };
+
+        let mut parts = vec![];
+        for part in &record.parts {
+            let read = reader.next(&data_path(record.id, part.number)).await?;
+            let staged =
+                ChunkMutation::insert_many_staged(db_conn, config.database.chunk_size(), read)
+                    .await?;
+
+            parts.push((part.to_model(record.id)?, staged));
+        }
+
+        let db_txn = db_conn.begin().await?;
+        let (_, version) = ObjectMutation::upsert_imported_also_version(
+            &db_txn,
+            bucket.id,
+            record.key.clone(),
+            record.to_model(data)?,
+            parts,
+            record.latest,
+        )
+        .await?;
+        if !record.tags.is_empty() {
+            TagSetMutation::upsert_with_tag(
+                &db_txn,
+                None,
+                None,
+                Some(version.id),
+                record.tags.into_iter(),
+            )
+            .await?;
+        }
+        db_txn.commit().await?;
+
+        count += 1;
+    }
+
+    info!(bucket = name, versions = count, "imported bucket");
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    #[arg(long, global = true, env = "MINIL_CONFIG")]
+    pub(crate) config: Option<PathBuf>,
+
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_override)]
+    pub(crate) overrides: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, Default, Subcommand)]
+pub(crate) enum Command {
+    #[default]
+    Serve,
+
+    Fsck {
+        #[arg(long)]
+        repair: bool,
+    },
+
+    Export {
+        bucket: String,
+
+        path: PathBuf,
+
+        #[arg(long, default_value = "minil")]
+        owner: String,
+
+        #[arg(long)]
+        versions: bool,
+
+        #[arg(long)]
+        dir: bool,
+    },
+
+    Import {
+        path: PathBuf,
+
+        #[arg(long)]
+        bucket: Option<String>,
+
+        #[arg(long, default_value = "minil")]
+        owner: String,
+    },
+    Seed {
+        path: PathBuf,
+
+        bucket: String,
+    },
+
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+
+    Owner {
+        #[command(subcommand)]
+        command: OwnerCommand,
+    },
+
+    Bucket {
+        #[command(subcommand)]
+        command: BucketCommand,
+    },
+
+    Gc,
+
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum MigrateCommand {
+    Up {
+        #[arg(long)]
+        steps: Option<u32>,
+    },
+
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum OwnerCommand {
+    Add { name: String },
+
+    List,
+
+    RotateKey { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum BucketCommand {
+    List {
+        #[arg(long)]
+        owner: Option<String>,
+    },
+
+    Create {
+        name: String,
+
+        #[arg(long, default_value = "minil")]
+        owner: String,
+    },
+
+    Delete {
+        name: String,
+
+        #[arg(long, default_value = "minil")]
+        owner: String,
+
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ConfigCommand {
+    Print {
+        #[arg(long, value_enum, default_value_t)]
+        format: ConfigFormat,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+pub(crate) enum ConfigFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+fn parse_override(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("invalid KEY=VALUE: no `=` found in `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_override() {
+        assert_eq!(
+            parse_override(" admin.token =a=b"),
+            Ok(("admin.token".to_owned(), "a=b".to_owned()))
+        );
+        assert_eq!(
+            parse_override("log.otlp="),
+            Ok(("log.otlp".to_owned(), String::new()))
+        );
+        assert!(parse_override("admin.token").is_err());
+    }
+
+    #[test]
+    fn test_parse_cli() {
+        let cli = Cli::try_parse_from(["minil"]).unwrap();
+        assert!(cli.command.is_none());
+        assert!(cli.overrides.is_empty());
+
+        let cli = Cli::try_parse_from([
+            "minil",
+            "export",
+            "bucket",
+            "out.tar",
+            "--versions",
+            "--set",
+            "database.name=other",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.overrides,
+            [("database.name".to_owned(), "other".to_owned())]
+        );
+        let Some(Command::Export {
+            bucket,
+            owner,
+            versions,
+            dir,
+            ..
+        }) = cli.command
+        else {
+            panic!("expected export command");
+        };
+        assert_eq!(bucket, "bucket");
+        assert_eq!(owner, "minil");
+        assert!(versions);
+        assert!(!dir);
+
+        assert!(Cli::try_parse_from(["minil", "--set", "database.name"]).is_err());
+        assert!(Cli::try_parse_from(["minil", "migrate"]).is_err());
+    }
+}
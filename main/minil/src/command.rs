@@ -0,0 +1,155 @@
+use std::process;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use minil_config::AppConfig;
+use minil_entity::owner;
+use minil_migration::Migrator;
+use minil_migration::MigratorTrait;
+use minil_service::ChunkStorage;
+use minil_service::prelude::*;
+use sea_orm::DbConn;
+
+use crate::cli::BucketCommand;
+use crate::cli::MigrateCommand;
+use crate::cli::OwnerCommand;
+
+pub(crate) async fn migrate(db_conn: &DbConn, command: MigrateCommand) {
+    match command {
+        MigrateCommand::Up { steps } => Migrator::up(db_conn, steps)
+            .await
+            .expect("failed to apply migrations"),
+        MigrateCommand::Down { steps } => Migrator::down(db_conn, Some(steps))
+            .await
+            .expect("failed to revert migrations"),
+        MigrateCommand::Status => {
+            for migration in Migrator::get_migration_with_status(db_conn)
+                .await
+                .expect("failed to get migrations")
+            {
+                println!("{}\t{}", migration.status(), migration.name());
+            }
+        }
+    }
+}
+
+pub(crate) async fn owner(db_conn: &DbConn, command: OwnerCommand) {
+    match command {
+        OwnerCommand::Add { name } => {
+            let Some(owner) = OwnerMutation::insert(db_conn, name.clone())
+                .await
+                .expect("failed to add owner")
+            else {
+                fail(&format!("owner already exists: {name}"));
+            };
+            print_keys(&owner);
+        }
+        OwnerCommand::List => {
+            for owner in OwnerQuery::find_many(db_conn)
+                .await
+                .expect("failed to list owners")
+            {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    owner.name,
+                    owner.access_key.as_deref().unwrap_or("-"),
+                    owner.usage_size,
+                    owner.usage_objects,
+                );
+            }
+        }
+        OwnerCommand::RotateKey { name } => {
+            let Some(owner) = OwnerMutation::update_keys(db_conn, &name)
+                .await
+                .expect("failed to rotate owner key")
+            else {
+                fail(&format!("no such owner: {name}"));
+            };
+            print_keys(&owner);
+        }
+    }
+}
+
+pub(crate) async fn bucket(db_conn: &DbConn, command: BucketCommand) {
+    match command {
+        BucketCommand::List { owner } => {
+            for (bucket, bucket_owner) in BucketQuery::find_all_also_owner(db_conn)
+                .await
+                .expect("failed to list buckets")
+            {
+                let bucket_owner = bucket_owner.map(|owner| owner.name).unwrap_or_default();
+                if owner.as_ref().is_some_and(|owner| *owner != bucket_owner) {
+                    continue;
+                }
+                println!(
+                    "{bucket_owner}\t{}\t{}\t{}\t{}",
+                    bucket.name, bucket.usage_size, bucket.usage_objects, bucket.created_at,
+                );
+            }
+        }
+        BucketCommand::Create { name, owner } => {
+            let owner = find_owner(db_conn, &owner).await;
+            if BucketMutation::insert(db_conn, owner.id, name.clone())
+                .await
+                .expect("failed to create bucket")
+                .is_none()
+            {
+                fail(&format!("bucket already exists: {name}"));
+            }
+            println!("created: {name}");
+        }
+        BucketCommand::Delete { name, owner, force } => {
+            let owner = find_owner(db_conn, &owner).await;
+            let Some(bucket) = BucketQuery::find(db_conn, owner.id, &name)
+                .await
+                .expect("failed to find bucket")
+            else {
+                fail(&format!("no such bucket: {name}"));
+            };
+            if bucket.usage_objects > 0 && !force {
+                fail(&format!(
+                    "bucket not empty: {name} ({} objects), use --force to delete",
+                    bucket.usage_objects,
+                ));
+            }
+            BucketMutation::delete(db_conn, owner.id, &name)
+                .await
+                .expect("failed to delete bucket");
+            println!("deleted: {name}");
+        }
+    }
+}
+
+pub(crate) async fn gc(db_conn: &DbConn, chunk_storage: &ChunkStorage, config: &AppConfig) {
+    let before = SystemTime::now() - Duration::from_secs(config.database.staging_ttl);
+    let (staged, orphaned) = crate::collect_garbage(db_conn, chunk_storage, before)
+        .await
+        .expect("failed to collect garbage");
+
+    println!("{staged} staging sessions deleted");
+    println!("{orphaned} orphaned chunks deleted");
+}
+
+async fn find_owner(db_conn: &DbConn, name: &str) -> owner::Model {
+    OwnerQuery::find(db_conn, name)
+        .await
+        .expect("failed to find owner")
+        .unwrap_or_else(|| fail(&format!("no such owner: {name}")))
+}
+
+fn print_keys(owner: &owner::Model) {
+    println!("name: {}", owner.name);
+    println!(
+        "access key: {}",
+        owner.access_key.as_deref().unwrap_or_default()
+    );
+    println!(
+        "secret key: {}",
+        owner.secret_key.as_deref().unwrap_or_default()
+    );
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
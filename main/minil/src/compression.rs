@@ -0,0 +1,186 @@
+use axum::body::HttpBody;
+use axum::http::Response;
+use axum::http::StatusCode;
+use axum::http::header;
+use mime::Mime;
+use minil_config::AppConfig;
+use tower_http::compression::Predicate;
+use tower_http::compression::predicate::SizeAbove;
+
+use crate::metrics::Operation;
+
+const OBJECT_OPERATIONS: &[&str] = &["GetObject"];
+const LISTING_OPERATIONS: &[&str] = &[
+    "ListBuckets",
+    "ListMultipartUploads",
+    "ListObjectVersions",
+    "ListObjects",
+    "ListObjectsV2",
+    "ListParts",
+];
+const INCOMPRESSIBLE_TYPES: &[&str] = &[
+    "application/gzip",
+    "application/octet-stream",
+    "application/vnd.rar",
+    "application/x-7z-compressed",
+    "application/x-bzip2",
+    "application/x-compress",
+    "application/x-gzip",
+    "application/x-rar-compressed",
+    "application/x-xz",
+    "application/zip",
+    "application/zstd",
+];
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompressPredicate {
+    enabled: bool,
+    objects: bool,
+    size_above: SizeAbove,
+}
+
+impl CompressPredicate {
+    pub(crate) fn new(config: &AppConfig) -> Self {
+        let compression = &config.server.compression;
+
+        Self {
+            enabled: compression.enabled,
+            objects: compression.objects,
+            size_above: SizeAbove::new(
+                compression
+                    .min_size
+                    .as_u64()
+                    .try_into()
+                    .expect("invalid min size"),
+            ),
+        }
+    }
+}
+
+impl Predicate for CompressPredicate {
+    fn should_compress<B: HttpBody>(&self, response: &Response<B>) -> bool {
+        if !self.enabled
+            || response.status() == StatusCode::PARTIAL_CONTENT
+            || response.headers().contains_key(header::CONTENT_RANGE)
+            || response.headers().contains_key(header::CONTENT_ENCODING)
+            || !self.size_above.should_compress(response)
+        {
+            return false;
+        }
+        let Some(content_type) = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+        else {
+            return false;
+        };
+
+        let Some(Operation(operation)) = response.extensions().get::<Operation>() else {
+            return false;
+        };
+
+        if OBJECT_OPERATIONS.contains(&&**operation) {
+            response.status().is_success() && self.objects && !is_incompressible(&content_type)
+        } else {
+            LISTING_OPERATIONS.contains(&&**operation) && is_xml(&content_type)
+        }
+    }
+}
+
+fn is_xml(content_type: &Mime) -> bool {
+    content_type.subtype() == mime::XML || content_type.suffix() == Some(mime::XML)
+}
+
+fn is_incompressible(content_type: &Mime) -> bool {
+    match content_type.type_() {
+        mime::IMAGE => content_type.suffix() != Some(mime::XML),
+        mime::AUDIO | mime::VIDEO => true,
+        _ => INCOMPRESSIBLE_TYPES.contains(&content_type.essence_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+
+    use super::*;
+
+    fn find_predicate(objects: bool) -> CompressPredicate {
+        let mut config = AppConfig::default();
+        config.server.compression.objects = objects;
+
+        CompressPredicate::new(&config)
+    }
+
+    fn find_response(operation: Option<&str>, content_type: &str) -> Response<Body> {
+        let mut response = Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(vec![b'a'; 4096]))
+            .unwrap();
+        if let Some(operation) = operation {
+            response
+                .extensions_mut()
+                .insert(Operation(Arc::from(operation)));
+        }
+
+        response
+    }
+
+    #[test]
+    fn test_should_compress_listings() {
+        let predicate = find_predicate(false);
+
+        for operation in LISTING_OPERATIONS {
+            let response = find_response(Some(operation), "application/xml");
+            assert!(predicate.should_compress(&response), "{operation}");
+        }
+        assert!(!predicate.should_compress(&find_response(Some("ListObjects"), "text/plain")));
+        assert!(!predicate.should_compress(&find_response(None, "application/xml")));
+        assert!(
+            !predicate.should_compress(&find_response(Some("GetBucketTagging"), "application/xml"))
+        );
+    }
+
+    #[test]
+    fn test_should_compress_objects() {
+        let predicate = find_predicate(true);
+        assert!(predicate.should_compress(&find_response(Some("GetObject"), "text/plain")));
+        assert!(predicate.should_compress(&find_response(Some("GetObject"), "image/svg+xml")));
+        assert!(!predicate.should_compress(&find_response(Some("GetObject"), "image/png")));
+        assert!(!predicate.should_compress(&find_response(Some("GetObject"), "application/zip")));
+        assert!(!predicate.should_compress(&find_response(Some("HeadObject"), "application/xml")));
+
+        let predicate = find_predicate(false);
+        assert!(!predicate.should_compress(&find_response(Some("GetObject"), "application/xml")));
+        assert!(!predicate.should_compress(&find_response(Some("HeadObject"), "application/xml")));
+    }
+
+    #[test]
+    fn test_should_compress_skips() {
+        let predicate = find_predicate(true);
+
+        let mut response = find_response(Some("GetObject"), "text/plain");
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        assert!(!predicate.should_compress(&response));
+
+        let mut response = find_response(Some("GetObject"), "text/plain");
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        assert!(!predicate.should_compress(&response));
+
+        let mut response = find_response(Some("ListObjects"), "application/xml");
+        response
+            .headers_mut()
+            .insert(header::CONTENT_ENCODING, "gzip".parse().unwrap());
+        assert!(!predicate.should_compress(&response));
+
+        let response = Response::builder()
+            .header(header::CONTENT_TYPE, "application/xml")
+            .extension(Operation(Arc::from("ListObjects")))
+            .body(Body::from("<small/>"))
+            .unwrap();
+        assert!(!predicate.should_compress(&response));
+    }
+}
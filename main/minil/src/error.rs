@@ -26,7 +26,6 @@ pub(crate) enum AppError {
     #[allow(dead_code)]
     BucketAlreadyExists,
     BucketAlreadyOwnedByYou,
-    #[allow(dead_code)]
     ConditionalRequestConflict,
     #[allow(dead_code)]
     EncryptionTypeMismatch,
@@ -34,6 +33,7 @@ pub(crate) enum AppError {
     IncompleteBody,
     #[deprecated]
     InternalError,
+    InvalidArgument,
     InvalidDigest,
     #[allow(dead_code)]
     InvalidObjectState,
@@ -42,7 +42,7 @@ pub(crate) enum AppError {
     InvalidPartOrder,
     InvalidRange,
     InvalidTag,
-    #[allow(dead_code)]
+    InvalidTargetBucketForLogging,
     InvalidWriteOffset,
     #[allow(dead_code)]
     MalformedXML,
@@ -53,11 +53,10 @@ pub(crate) enum AppError {
     NoSuchUpload,
     NoSuchVersion,
     NotImplemented,
-    #[allow(dead_code)]
     OperationAborted,
-    #[allow(dead_code)]
     PreconditionFailed,
-    #[allow(dead_code)]
+    QuotaExceeded,
+    SlowDown,
     TooManyParts,
 
     AxumError(axum::Error),
@@ -98,11 +97,13 @@ impl AppErrorDiscriminants {
             EncryptionTypeMismatch => EncryptionTypeMismatchOutput,
             IncompleteBody => IncompleteBodyOutput,
             InternalError => InternalErrorOutput,
+            InvalidArgument => InvalidArgumentOutput,
             InvalidDigest => InvalidDigestOutput,
             InvalidObjectState => InvalidObjectStateOutput,
             InvalidPart => InvalidPartOutput,
             InvalidPartOrder => InvalidPartOrderOutput,
             InvalidTag => InvalidTagOutput,
+            InvalidTargetBucketForLogging => InvalidTargetBucketForLoggingOutput,
             InvalidRange => InvalidRangeOutput,
             InvalidWriteOffset => InvalidWriteOffsetOutput,
             MalformedXML => MalformedXMLOutput,
@@ -115,6 +116,8 @@ impl AppErrorDiscriminants {
             NoSuchVersion => NoSuchVersionOutput,
             OperationAborted => OperationAbortedOutput,
             PreconditionFailed => PreconditionFailedOutput,
+            QuotaExceeded => QuotaExceededOutput,
+            SlowDown => SlowDownOutput,
             TooManyParts => TooManyPartsOutput,
             _ => [AxumError, DatabaseError, IoError],
         })
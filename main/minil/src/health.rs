@@ -0,0 +1,135 @@
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Extension;
+use axum::Json;
+use axum::Router;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use minil_migration::Migrator;
+use minil_migration::MigratorTrait;
+use minil_service::ChunkStorage;
+use sea_orm::DbConn;
+use serde::Serialize;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+use crate::state::AppState;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<E: Display> From<Result<(), E>> for Check {
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self {
+                status: Status::Ok,
+                error: None,
+            },
+            Err(err) => Self {
+                status: Status::Fail,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    status: Status,
+    database: Check,
+    migrations: Check,
+    storage: Check,
+    shutdown: Check,
+}
+
+pub(crate) fn live_router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new().route("/health/live", get(get_liveness))
+}
+
+pub(crate) fn router(state: AppState, draining: CancellationToken) -> Router {
+    live_router()
+        .route("/health/ready", get(get_readiness))
+        .layer(Extension(draining))
+        .with_state(state)
+}
+
+async fn get_liveness() -> Json<Check> {
+    Json(Ok::<_, String>(()).into())
+}
+
+async fn get_readiness(
+    State(db_conn): State<DbConn>,
+    State(chunk_storage): State<Arc<ChunkStorage>>,
+    Extension(draining): Extension<CancellationToken>,
+) -> (StatusCode, Json<Readiness>) {
+    let (database, migrations, storage) = tokio::join!(
+        check_database(&db_conn),
+        check_migrations(&db_conn),
+        check_storage(&chunk_storage),
+    );
+    let shutdown = Check::from(if draining.is_cancelled() {
+        Err("shutting down")
+    } else {
+        Ok(())
+    });
+
+    let status = if [&database, &migrations, &storage, &shutdown]
+        .iter()
+        .all(|check| check.status == Status::Ok)
+    {
+        Status::Ok
+    } else {
+        Status::Fail
+    };
+    let readiness = Readiness {
+        status,
+        database,
+        migrations,
+        storage,
+        shutdown,
+    };
+
+    match status {
+        Status::Ok => (StatusCode::OK, Json(readiness)),
+        Status::Fail => (StatusCode::SERVICE_UNAVAILABLE, Json(readiness)),
+    }
+}
+
+async fn check_database(db_conn: &DbConn) -> Check {
+    match time::timeout(CHECK_TIMEOUT, db_conn.ping()).await {
+        Ok(result) => result.into(),
+        Err(err) => Err(err).into(),
+    }
+}
+
+async fn check_migrations(db_conn: &DbConn) -> Check {
+    match time::timeout(CHECK_TIMEOUT, Migrator::get_pending_migrations(db_conn)).await {
+        Ok(Ok(migrations)) if migrations.is_empty() => Ok::<_, String>(()).into(),
+        Ok(Ok(migrations)) => Err(format!("{} pending migrations", migrations.len())).into(),
+        Ok(Err(err)) => Err(err).into(),
+        Err(err) => Err(err).into(),
+    }
+}
+
+async fn check_storage(chunk_storage: &ChunkStorage) -> Check {
+    match time::timeout(CHECK_TIMEOUT, chunk_storage.probe()).await {
+        Ok(result) => result.into(),
+        Err(err) => Err(err).into(),
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use axum::extract::ConnectInfo;
+use axum::extract::FromRequestParts;
+use axum::extract::RawPathParams;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::http::header;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::IntoResponse as _;
+use axum::response::Response;
+use minil_config::AppConfig;
+use minil_config::RateLimit;
+use minil_entity::owner;
+use parking_lot::Mutex;
+
+use crate::error::AppError;
+use crate::tls::ClientOwner;
+
+const MAX_ENTRIES: usize = 100_000;
+const CONCURRENCY_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Scope {
+    Ip,
+    AccessKey,
+    Owner,
+    Bucket,
+}
+
+#[derive(Debug)]
+struct Entry {
+    tokens: f64,
+    updated: Instant,
+    in_flight: usize,
+}
+
+impl Entry {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst(),
+            updated: now,
+            in_flight: 0,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        if let Some(rate) = limit.rate {
+            let elapsed = now.duration_since(self.updated).as_secs_f64();
+            self.tokens = elapsed.mul_add(rate, self.tokens).min(limit.burst());
+        }
+        self.updated = now;
+    }
+
+    fn retry_after(&self, limit: &RateLimit) -> Option<Duration> {
+        if limit
+            .concurrency
+            .is_some_and(|concurrency| self.in_flight >= concurrency)
+        {
+            return Some(CONCURRENCY_RETRY_AFTER);
+        }
+
+        limit
+            .rate
+            .filter(|_| self.tokens < 1.0)
+            .map(|rate| Duration::from_secs_f64((1.0 - self.tokens) / rate))
+    }
+
+    fn is_idle(&self, limit: &RateLimit) -> bool {
+        self.in_flight == 0 && self.tokens >= limit.burst()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Limiter {
+    config: Arc<AppConfig>,
+    entries: Arc<Mutex<HashMap<(Scope, String), Entry>>>,
+}
+
+impl Limiter {
+    pub(crate) fn new(config: Arc<AppConfig>) -> Self {
+        Self {
+            config,
+            entries: Arc::default(),
+        }
+    }
+
+    fn limit(&self, scope: Scope) -> &RateLimit {
+        let limit = &self.config.limit;
+        match scope {
+            Scope::Ip => &limit.ip,
+            Scope::AccessKey => &limit.access_key,
+            Scope::Owner => &limit.owner,
+            Scope::Bucket => &limit.bucket,
+        }
+    }
+
+    async fn run(&self, keys: Vec<(Scope, String)>, request: Request, next: Next) -> Response {
+        let keys = keys
+            .into_iter()
+            .filter(|(scope, _)| self.limit(*scope).is_enabled())
+            .collect();
+
+        match self.try_acquire(keys) {
+            Ok(_permit) => next.run(request).await,
+            Err(retry_after) => {
+                let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                (
+                    [(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)))],
+                    AppError::SlowDown,
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    fn try_acquire(&self, keys: Vec<(Scope, String)>) -> Result<Permit, Duration> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|(scope, _), entry| {
+                let limit = self.limit(*scope);
+                entry.refill(limit, now);
+                !entry.is_idle(limit)
+            });
+        }
+
+        let mut retry_after = None;
+        for key in &keys {
+            let limit = self.limit(key.0);
+            let entry = entries
+                .entry(key.clone())
+                .or_insert_with(|| Entry::new(limit, now));
+            entry.refill(limit, now);
+            retry_after = retry_after.max(entry.retry_after(limit));
+        }
+        if let Some(retry_after) = retry_after {
+            return Err(retry_after);
+        }
+
+        for key in &keys {
+            let limit = self.limit(key.0);
+            let entry = entries.get_mut(key).expect("missing limit entry");
+            if limit.rate.is_some() {
+                entry.tokens -= 1.0;
+            }
+            entry.in_flight += 1;
+        }
+
+        Ok(Permit {
+            entries: Arc::clone(&self.entries),
+            keys,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Permit {
+    entries: Arc<Mutex<HashMap<(Scope, String), Entry>>>,
+    keys: Vec<(Scope, String)>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut entries = self.entries.lock();
+        for key in &self.keys {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.in_flight -= 1;
+            }
+        }
+    }
+}
+
+pub(crate) async fn limit_requests(
+    State(limiter): State<Limiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let keys = find_client_keys(&parts);
+
+    limiter
+        .run(keys, Request::from_parts(parts, body), next)
+        .await
+}
+
+pub(crate) async fn limit_owner_requests(
+    State(limiter): State<Limiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = request.into_parts();
+    let owner = parts
+        .extensions
+        .get::<owner::Model>()
+        .map(|owner| owner.name.clone());
+    let keys = match owner {
+        Some(owner) => find_owner_keys(&mut parts, &owner).await,
+        None => vec![],
+    };
+
+    limiter
+        .run(keys, Request::from_parts(parts, body), next)
+        .await
+}
+
+fn find_client_keys(parts: &Parts) -> Vec<(Scope, String)> {
+    let mut keys = vec![];
+    if let Some(ConnectInfo(client_owner)) = parts.extensions.get::<ConnectInfo<ClientOwner>>() {
+        keys.push((Scope::Ip, client_owner.addr.ip().to_string()));
+    }
+    if let Some(access_key) = find_access_key(parts) {
+        keys.push((Scope::AccessKey, access_key));
+    }
+
+    keys
+}
+
+async fn find_owner_keys(parts: &mut Parts, owner: &str) -> Vec<(Scope, String)> {
+    let mut keys = vec![];
+    if let Ok(params) = RawPathParams::from_request_parts(parts, &()).await
+        && let Some((_, bucket)) = params.iter().find(|(name, _)| *name == "Bucket")
+    {
+        keys.push((Scope::Bucket, format!("{owner}/{bucket}")));
+    }
+    keys.push((Scope::Owner, owner.to_owned()));
+
+    keys
+}
+
+fn find_access_key(parts: &Parts) -> Option<String> {
+    if let Some(authorization) = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+    {
+        let access_key = if let Some(v4) = authorization.strip_prefix("AWS4-HMAC-SHA256 ") {
+            v4.split(',')
+                .find_map(|field| field.trim().strip_prefix("Credential="))
+                .and_then(|credential| credential.split('/').next())
+        } else {
+            authorization
+                .strip_prefix("AWS ")
+                .and_then(|v2| v2.split(':').next())
+        };
+        return access_key.map(ToOwned::to_owned);
+    }
+
+    parts.uri.query()?.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        match name {
+            "X-Amz-Credential" => value.split(['/', '%']).next().map(ToOwned::to_owned),
+            "AWSAccessKeyId" => Some(value.to_owned()),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Extension;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::middleware;
+    use axum::routing::get;
+    use sea_orm::prelude::DateTimeUtc;
+    use tower::ServiceExt as _;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn find_owner(name: &str) -> owner::Model {
+        owner::Model {
+            id: Uuid::new_v4(),
+            name: name.to_owned(),
+            quota_size: None,
+            quota_objects: None,
+            usage_size: 0,
+            usage_objects: 0,
+            access_key: None,
+            secret_key: None,
+            disabled: false,
+            created_at: DateTimeUtc::default(),
+            updated_at: None,
+        }
+    }
+
+    fn find_parts(uri: &str, authorization: Option<&str>) -> Parts {
+        let mut request = Request::get(uri);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+
+        request.body(Body::empty()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_find_access_key() {
+        let find = |uri, authorization| find_access_key(&find_parts(uri, authorization));
+
+        assert_eq!(
+            find(
+                "/bkt",
+                Some(
+                    "AWS4-HMAC-SHA256 Credential=AKID/20261019/us-east-1/s3/aws4_request, \
+                     SignedHeaders=host, Signature=abc"
+                )
+            ),
+            Some("AKID".to_owned())
+        );
+        assert_eq!(
+            find("/bkt", Some("AWS AKID:signature")),
+            Some("AKID".to_owned())
+        );
+        assert_eq!(find("/bkt", Some("Bearer token")), None);
+        assert_eq!(
+            find(
+                "/bkt/key?X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Credential=AKID%2F20261019%2Fus-east-1",
+                None
+            ),
+            Some("AKID".to_owned())
+        );
+        assert_eq!(
+            find("/bkt/key?AWSAccessKeyId=AKID&Signature=abc", None),
+            Some("AKID".to_owned())
+        );
+        assert_eq!(find("/bkt/key?versionId=1", None), None);
+        assert_eq!(find("/bkt/key", None), None);
+    }
+
+    #[test]
+    fn test_find_client_keys() {
+        let mut parts = find_parts("/bkt", Some("AWS AKID:signature"));
+        assert_eq!(
+            find_client_keys(&parts),
+            [(Scope::AccessKey, "AKID".to_owned())]
+        );
+
+        parts.extensions.insert(ConnectInfo(ClientOwner {
+            addr: "192.0.2.1:4000".parse().unwrap(),
+            name: Some("alice".to_owned()),
+        }));
+        assert_eq!(
+            find_client_keys(&parts),
+            [
+                (Scope::Ip, "192.0.2.1".to_owned()),
+                (Scope::AccessKey, "AKID".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refill() {
+        let limit = RateLimit {
+            rate: Some(2.0),
+            burst: Some(4),
+            concurrency: None,
+        };
+        let now = Instant::now();
+        let mut entry = Entry::new(&limit, now);
+        assert!((entry.tokens - 4.0).abs() < f64::EPSILON);
+
+        entry.tokens = 0.0;
+        entry.refill(&limit, now + Duration::from_millis(500));
+        assert!((entry.tokens - 1.0).abs() < 1e-9);
+
+        entry.refill(&limit, now + Duration::from_secs(10));
+        assert!((entry.tokens - 4.0).abs() < f64::EPSILON);
+        assert!(entry.is_idle(&limit));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let limit = RateLimit {
+            rate: Some(2.0),
+            burst: None,
+            concurrency: Some(1),
+        };
+        let mut entry = Entry::new(&limit, Instant::now());
+        assert_eq!(entry.retry_after(&limit), None);
+
+        entry.tokens = 0.5;
+        assert_eq!(entry.retry_after(&limit), Some(Duration::from_millis(250)));
+
+        entry.in_flight = 1;
+        assert_eq!(entry.retry_after(&limit), Some(CONCURRENCY_RETRY_AFTER));
+        assert!(!entry.is_idle(&limit));
+    }
+
+    #[test]
+    fn test_try_acquire() {
+        let mut config = AppConfig::default();
+        config.limit.owner.concurrency = Some(1);
+        let limiter = Limiter::new(Arc::new(config));
+        let keys = vec![(Scope::Owner, "minil".to_owned())];
+
+        let permit = limiter.try_acquire(keys.clone()).unwrap();
+        assert_eq!(
+            limiter.try_acquire(keys.clone()).unwrap_err(),
+            CONCURRENCY_RETRY_AFTER
+        );
+        assert!(
+            limiter
+                .try_acquire(vec![(Scope::Owner, "other".to_owned())])
+                .is_ok()
+        );
+
+        drop(permit);
+        assert!(limiter.try_acquire(keys).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_limit_owner_requests() {
+        let mut config = AppConfig::default();
+        config.limit.bucket.rate = Some(1.0);
+        let limiter = Limiter::new(Arc::new(config));
+        let router = Router::new()
+            .route("/{Bucket}/{*Key}", get(async || StatusCode::OK))
+            .layer(middleware::from_fn_with_state(
+                limiter.clone(),
+                limit_owner_requests,
+            ));
+        let status = async |owner: &str, uri: &str| {
+            let router = router.clone().layer(Extension(find_owner(owner)));
+            let request = Request::get(uri).body(Body::empty()).unwrap();
+            router.oneshot(request).await.unwrap().status()
+        };
+
+        assert_eq!(status("alice", "/bkt/key").await, StatusCode::OK);
+        assert_ne!(status("alice", "/bkt/key").await, StatusCode::OK);
+        assert_eq!(status("alice", "/other/key").await, StatusCode::OK);
+        assert_eq!(status("bob", "/bkt/key").await, StatusCode::OK);
+        assert!(
+            limiter
+                .entries
+                .lock()
+                .contains_key(&(Scope::Bucket, "bob/bkt".to_owned()))
+        );
+    }
+}
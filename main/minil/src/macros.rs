@@ -35,10 +35,19 @@ macro_rules! _app_define_handler {
 
 macro_rules! app_define_handler {
     ($handler:path) => {
-        $handler
+        $crate::metrics::operation($handler, ::core::stringify!($handler))
     };
-    ({$($handler:tt)*}) => {
-        ::axum_filter_router::axum_filter_handler!($crate::state::AppState {$($handler)*})
+    ({
+        $($filter:ident($($arg:tt)*) => $handler:expr,)*
+        _ => $def_handler:expr $(,)?
+    }) => {
+        ::axum_filter_router::axum_filter_handler!($crate::state::AppState {
+            $($filter($($arg)*) => $crate::metrics::operation(
+                $handler,
+                ::core::stringify!($handler),
+            ),)*
+            _ => $crate::metrics::operation($def_handler, ::core::stringify!($def_handler)),
+        })
     };
 }
 
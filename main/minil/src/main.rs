@@ -1,14 +1,34 @@
+mod access_log;
+mod admin;
+mod archive;
+mod cli;
+mod command;
+mod compression;
 mod database_transaction;
 mod error;
+mod health;
+mod limit;
 mod macros;
+mod metrics;
+mod mount;
 mod state;
+mod telemetry;
+mod tls;
 mod utils;
+mod virtual_host;
 
 use std::collections::HashSet;
 use std::convert;
 use std::env;
+use std::fs;
 use std::future;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
 use std::pin::pin;
+use std::process;
+use std::process::ExitCode;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
@@ -20,9 +40,11 @@ use axum::Extension;
 use axum::Router;
 use axum::ServiceExt;
 use axum::body::Body;
+use axum::extract::ConnectInfo;
 use axum::extract::Request;
 use axum::extract::State;
 use axum::handler::Handler;
+use axum::http::HeaderMap;
 use axum::http::HeaderName;
 use axum::http::HeaderValue;
 use axum::http::StatusCode;
@@ -31,7 +53,7 @@ use axum::middleware::Next;
 use axum::response::Response;
 use axum_s3::operation::*;
 use axum_s3::utils::CommonExtInput;
-use futures::StreamExt;
+use clap::Parser;
 use futures::TryStreamExt;
 use http_content_range::ContentRangeBytes;
 use http_digest::DigestMd5;
@@ -40,13 +62,24 @@ use md5::Digest;
 use md5::Md5;
 use mime::Mime;
 use minil_config::AppConfig;
+use minil_entity::bucket;
+use minil_entity::owner;
+use minil_entity::version;
 use minil_migration::Migrator;
 use minil_migration::MigratorTrait;
+use minil_service::ChunkCache;
+use minil_service::ChunkStorage;
+use minil_service::ChunkTier;
+use minil_service::InsRes;
 use minil_service::prelude::*;
+use parking_lot::RwLock;
 use sea_orm::ConnectOptions;
+use sea_orm::ConnectionTrait;
 use sea_orm::Database;
 use sea_orm::DbConn;
+use sea_orm::SqlErr;
 use sea_orm::TransactionTrait;
+use sea_orm::prelude::DateTimeUtc;
 use serde_s3::operation::*;
 use serde_s3::types::Bucket;
 use serde_s3::types::BucketLocationConstraint;
@@ -55,40 +88,66 @@ use serde_s3::types::CommonPrefix;
 use serde_s3::types::DeleteMarkerEntry;
 use serde_s3::types::EncodingType;
 use serde_s3::types::Initiator;
+use serde_s3::types::LoggingEnabled;
 use serde_s3::types::MfaDeleteStatus;
 use serde_s3::types::MultipartUpload;
 use serde_s3::types::Object;
 use serde_s3::types::ObjectVersion;
 use serde_s3::types::Owner;
 use serde_s3::types::Part;
+use serde_s3::types::StorageClass;
 use serde_s3::types::Tag;
 use serde_s3::utils::DeleteMarkerOrVersion;
 use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
 use tower::Layer;
 use tower::ServiceBuilder;
+use tower::util::MapRequestLayer;
 use tower_http::BoxError;
 use tower_http::ServiceBuilderExt;
+use tower_http::compression::CompressionLayer;
 use tower_http::normalize_path::NormalizePathLayer;
 use tower_http::request_id::MakeRequestUuid;
 use tower_http::set_header::SetRequestHeaderLayer;
+use tower_http::trace::TraceLayer;
+use tracing::Level;
 use tracing::debug;
+use tracing::error;
 use tracing::info;
 use tracing::instrument;
+use tracing::warn;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::FilterExt;
 use tracing_subscriber::filter::Targets;
 use tracing_subscriber::prelude::*;
 use uuid::Uuid;
 
+use crate::access_log::AccessLog;
+use crate::access_log::LogDelivery;
+use crate::cli::Cli;
+use crate::cli::Command;
+use crate::cli::ConfigCommand;
+use crate::cli::ConfigFormat;
+use crate::compression::CompressPredicate;
 use crate::database_transaction::DbTxn;
 use crate::error::AppError;
 use crate::error::AppErrorDiscriminants;
 use crate::error::AppResult;
+use crate::limit::Limiter;
 use crate::macros::app_define_handlers;
 use crate::macros::app_ensure_eq;
 use crate::macros::app_ensure_matches;
 use crate::macros::app_validate_owner;
 use crate::state::AppState;
+use crate::telemetry::TracerGuard;
+use crate::tls::ClientOwner;
+use crate::tls::TlsListener;
 use crate::utils::BodyExt;
 use crate::utils::ServiceBuilderExt as _;
 
@@ -105,22 +164,144 @@ const PROCESS_TIME: &str = "x-process-time";
 
 const NODE_ID_HEADER: HeaderName = HeaderName::from_static("x-amz-id-2");
 const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-amz-request-id");
+const VERSION_ID_HEADER: HeaderName = HeaderName::from_static("x-amz-version-id");
+const QUOTA_SIZE_HEADER: HeaderName = HeaderName::from_static("x-minil-quota-size");
+const QUOTA_OBJECTS_HEADER: HeaderName = HeaderName::from_static("x-minil-quota-objects");
+const USAGE_SIZE_HEADER: HeaderName = HeaderName::from_static("x-minil-usage-size");
+const USAGE_OBJECTS_HEADER: HeaderName = HeaderName::from_static("x-minil-usage-objects");
 
 #[tokio::main]
-async fn main() {
-    let config = dbg!(init_config());
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = init_config(&cli);
+    let command = cli.command.unwrap_or_default();
+    if let Command::Config { command } = command {
+        match command {
+            ConfigCommand::Print { format } => print_config(config, format),
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let config = Arc::new(config);
     let _log_guard = init_trace(&config);
-    let db = init_db(&config).await;
+    let db = init_db(&config, !matches!(command, Command::Migrate { .. })).await;
+
+    match command {
+        Command::Serve => serve(config, db).await,
+        Command::Fsck { repair } => return fsck(&db, &config, repair).await,
+        Command::Export {
+            bucket,
+            path,
+            owner,
+            versions,
+            dir,
+        } => archive::export(
+            &db,
+            Arc::new(init_chunk_storage(&config)),
+            &owner,
+            &bucket,
+            &path,
+            versions,
+            dir,
+        )
+        .await
+        .expect("failed to export bucket"),
+        Command::Import {
+            path,
+            bucket,
+            owner,
+        } => archive::import(&db, &config, &owner, &path, bucket)
+            .await
+            .expect("failed to import bucket"),
+        Command::Seed { path, bucket } => mount::seed(&db, &config, &path, &bucket)
+            .await
+            .expect("failed to seed bucket"),
+        Command::Migrate { command } => command::migrate(&db, command).await,
+        Command::Owner { command } => command::owner(&db, command).await,
+        Command::Bucket { command } => command::bucket(&db, command).await,
+        Command::Gc => command::gc(&db, &init_chunk_storage(&config), &config).await,
+        Command::Config { .. } => unreachable!(),
+    }
 
-    let state = AppState::new(db);
+    ExitCode::SUCCESS
+}
+
+async fn serve(config: Arc<AppConfig>, db: DbConn) {
+    tokio::spawn(sweep_staged_chunks(
+        db.clone(),
+        Duration::from_secs(config.database.staging_ttl),
+    ));
+
+    let chunk_cache = Arc::new(ChunkCache::new(config.cache.size));
+    tokio::spawn(log_chunk_cache_stats(
+        Arc::clone(&chunk_cache),
+        Duration::from_secs(config.cache.stats_interval),
+    ));
+
+    let chunk_storage = Arc::new(init_chunk_storage(&config));
+    if let Some(tier) = chunk_storage.tiers().pop() {
+        tokio::spawn(move_cold_versions(
+            db.clone(),
+            Arc::clone(&chunk_storage),
+            Arc::clone(&config),
+            tier,
+        ));
+    }
+
+    let scrub_lock = Arc::default();
+    tokio::spawn(scrub_versions(
+        db.clone(),
+        Arc::clone(&chunk_storage),
+        Arc::clone(&config),
+        Arc::clone(&scrub_lock),
+    ));
+
+    if let (Some(bucket), Some(path)) = (config.mount.bucket.clone(), config.mount.path.clone()) {
+        tokio::spawn(scan_mounted_bucket(
+            db.clone(),
+            bucket,
+            path,
+            Duration::from_secs(config.mount.interval),
+        ));
+    }
+
+    let (access_stream, _access_log_guard) = config
+        .log
+        .access
+        .as_ref()
+        .map(|access| {
+            let (writer, guard) = access
+                .stream
+                .to_writer()
+                .expect("failed to open access log stream");
+            ((writer, access.format), guard)
+        })
+        .unzip();
+    let log_delivery = LogDelivery::default();
+    tokio::spawn(deliver_access_logs(
+        db.clone(),
+        Arc::clone(&config),
+        log_delivery.clone(),
+        Duration::from_secs(config.log.delivery_interval),
+    ));
+    let access_log = AccessLog::new(access_stream, log_delivery.clone());
+
+    let limiter = Limiter::new(Arc::clone(&config));
+    let state = AppState::new(
+        Arc::clone(&config),
+        db,
+        chunk_cache,
+        chunk_storage,
+        scrub_lock,
+    );
     let node_id =
         Uuid::new_v8(NODE_NAME.as_bytes().try_into().expect("invalid node name")).to_string();
     let server = format!("{}-{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 
     let middleware = ServiceBuilder::new()
-        .trace_for_http()
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::make_span))
+        .layer(CompressionLayer::new().compress_when(CompressPredicate::new(&config)))
         .decompression()
-        // fixme .compression()
         .override_request_header(
             NODE_ID_HEADER,
             node_id.parse::<HeaderValue>().expect("invalid node id"),
@@ -135,9 +316,16 @@ async fn main() {
                 .expect("invalid server header"),
         )
         .middleware_fn(set_process_time)
+        .middleware_fn(telemetry::record_span)
+        .middleware_fn_with_state(access_log, access_log::record_access)
+        .middleware_fn(metrics::record_metrics)
         .middleware_fn(handle_app_err)
+        .middleware_fn_with_state(limiter.clone(), limit::limit_requests)
         .middleware_fn(validate_content_md5)
-        .middleware_fn_with_state(state.clone(), manage_db_txn);
+        .middleware_fn_with_state(state.clone(), manage_db_txn)
+        .middleware_fn(resolve_owner)
+        .middleware_fn_with_state(state.clone(), mount::reject_writes)
+        .middleware_fn_with_state(limiter, limit::limit_owner_requests);
 
     let content_type_value = "application/xml"
         .parse::<HeaderValue>()
@@ -149,6 +337,8 @@ async fn main() {
 
     let put_bucket_tagging_handler =
         put_bucket_tagging.layer(if_not_present_content_type_layer.clone());
+    let put_bucket_logging_handler =
+        put_bucket_logging.layer(if_not_present_content_type_layer.clone());
     let put_object_tagging_handler = put_object_tagging.layer(if_not_present_content_type_layer);
     let complete_multipart_upload_handler =
         complete_multipart_upload.layer(override_content_type_layer);
@@ -164,6 +354,7 @@ async fn main() {
         get("/{Bucket}") => {
             query("list-type", "2") => list_objects_v2,
             query("location", "") => get_bucket_location,
+            query("logging", "") => get_bucket_logging,
             query("versioning", "") => get_bucket_versioning,
             query("versions", "") => list_object_versions,
             query("tagging", "") => get_bucket_tagging,
@@ -173,6 +364,7 @@ async fn main() {
         head("/{Bucket}") => head_bucket,
         put("/{Bucket}") => {
             query("tagging", "") => put_bucket_tagging_handler,
+            query("logging", "") => put_bucket_logging_handler,
             query("versioning", "") => put_bucket_versioning,
             _ => create_bucket,
         },
@@ -195,6 +387,7 @@ async fn main() {
         },
         put("/{Bucket}/{*Key}") => {
             query("tagging", "") => put_object_tagging_handler,
+            query("renameObject", "") => rename_object,
             query("uploadId") => upload_part,
             _ => put_object
         },
@@ -207,24 +400,156 @@ async fn main() {
         // },
     })
     .method_not_allowed_fallback(async || AppError::MethodNotAllowed)
-    .with_state(state)
+    .with_state(state.clone())
     .layer(middleware);
-    let app = ServiceExt::<Request>::into_make_service(
-        NormalizePathLayer::trim_trailing_slash().layer(router),
-    );
 
-    let addr = config.server.to_socket();
-    let listener = TcpListener::bind(addr)
-        .await
-        .expect("failed to bind address");
-    info!("tcp listening on {addr}");
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+    let draining = CancellationToken::new();
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let draining = draining.clone();
+        let shutdown = shutdown.clone();
+        let drain = Duration::from_secs(config.server.drain);
+        async move {
+            shutdown_signal().await;
+            draining.cancel();
+            if !drain.is_zero() {
+                info!("draining for {drain:?}");
+                tokio::time::sleep(drain).await;
+            }
+            shutdown.cancel();
+        }
+    });
+
+    let router = Router::new()
+        .nest("/_minil", health::live_router())
+        .merge(router);
+    let domain = config.server.domain.clone();
+    let router = MapRequestLayer::new(move |request| match &domain {
+        Some(domain) => virtual_host::rewrite_request(domain, request),
+        None => request,
+    })
+    .layer(router);
+    let router = NormalizePathLayer::trim_trailing_slash().layer(router);
+
+    let acceptor = tls::load_acceptor(&config)
+        .expect("failed to load tls certificate")
+        .map(|acceptor| Arc::new(RwLock::new(acceptor)));
+    if let Some(acceptor) = &acceptor {
+        tokio::spawn(reload_tls_certificate(
+            Arc::clone(acceptor),
+            Arc::clone(&config),
+            Duration::from_secs(config.server.tls.interval),
+        ));
+    }
+
+    let mut servers = JoinSet::new();
+    for addr in config.server.to_sockets() {
+        let listener = TcpListener::bind(addr)
+            .await
+            .expect("failed to bind address");
+        let shutdown = shutdown.clone().cancelled_owned();
+        if let Some(acceptor) = &acceptor {
+            let listener = TlsListener::new(listener, Arc::clone(acceptor))
+                .expect("failed to create listener");
+            info!("tls listening on {addr}");
+            servers.spawn(
+                axum::serve(
+                    listener,
+                    ServiceExt::<Request>::into_make_service_with_connect_info::<ClientOwner>(
+                        router.clone(),
+                    ),
+                )
+                .with_graceful_shutdown(shutdown)
+                .into_future(),
+            );
+        } else {
+            info!("tcp listening on {addr}");
+            servers.spawn(
+                axum::serve(
+                    listener,
+                    ServiceExt::<Request>::into_make_service_with_connect_info::<ClientOwner>(
+                        router.clone(),
+                    ),
+                )
+                .with_graceful_shutdown(shutdown)
+                .into_future(),
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = &config.server.unix {
+        let listener = bind_unix(path).expect("failed to bind unix socket");
+        info!("unix listening on {}", path.display());
+        servers.spawn(
+            axum::serve(
+                listener,
+                ServiceExt::<Request>::into_make_service(router.clone()),
+            )
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future(),
+        );
+    }
+
+    if let Some(addr) = config.admin.socket {
+        let token = config.admin.token.clone().expect("admin token is required");
+        let router = health::router(state.clone(), draining)
+            .merge(admin::router(state.clone(), token))
+            .layer(ServiceBuilder::new().trace_for_http());
+        let router = NormalizePathLayer::trim_trailing_slash().layer(router);
+        let listener = TcpListener::bind(addr)
+            .await
+            .expect("failed to bind admin address");
+        info!("admin listening on {addr}");
+        servers.spawn(
+            axum::serve(listener, ServiceExt::<Request>::into_make_service(router))
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                .into_future(),
+        );
+    }
+
+    while let Some(result) = servers.join_next().await {
+        result
+            .expect("failed to join server")
+            .expect("failed to serve");
+    }
+
+    if let Err(err) = access_log::deliver(&state.db_conn, &config, &log_delivery).await {
+        error!(%err, "DeliveryError");
+    }
+}
+
+async fn fsck(db_conn: &DbConn, config: &AppConfig, repair: bool) -> ExitCode {
+    let staged_before = SystemTime::now() - Duration::from_secs(config.database.staging_ttl);
+    let issues = FsckQuery::check(db_conn)
         .await
-        .unwrap();
+        .expect("failed to check database");
+
+    let db_txn = db_conn.begin().await.expect("failed to begin transaction");
+    let mut unrepaired = 0;
+    for issue in &issues {
+        if repair
+            && FsckMutation::repair(&db_txn, issue, staged_before)
+                .await
+                .expect("failed to repair database")
+        {
+            println!("repaired: {issue}");
+        } else {
+            println!("{issue}");
+            unrepaired += 1;
+        }
+    }
+    db_txn.commit().await.expect("failed to commit transaction");
+
+    println!("{} issues, {unrepaired} unrepaired", issues.len());
+    if unrepaired > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-fn init_config() -> AppConfig {
+fn init_config(cli: &Cli) -> AppConfig {
     match env::var("RUST_ENV") {
         Ok(path) => dotenvy::from_path(path).expect("failed to load env file"),
         Err(_) => match dotenvy::dotenv() {
@@ -237,29 +562,65 @@ fn init_config() -> AppConfig {
         },
     }
 
-    AppConfig::try_new().expect("failed to load config")
+    let config =
+        AppConfig::try_load(cli.config.as_deref(), &cli.overrides).expect("failed to load config");
+    if let Err(err) = config.validate() {
+        eprintln!("invalid config:");
+        for error in err.errors {
+            eprintln!("  {error}");
+        }
+        process::exit(1);
+    }
+
+    config
+}
+
+fn print_config(mut config: AppConfig, format: ConfigFormat) {
+    config.redact();
+    let config = match format {
+        ConfigFormat::Toml => toml::to_string_pretty(&config).expect("failed to serialize config"),
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(&config).expect("failed to serialize config")
+        }
+    };
+
+    println!("{config}");
 }
 
-fn init_trace(config: &AppConfig) -> WorkerGuard {
-    let (writer, guard) = config.log.stream.to_writer();
+fn init_trace(config: &AppConfig) -> (WorkerGuard, Option<TracerGuard>) {
+    let (writer, guard) = config
+        .log
+        .stream
+        .to_writer()
+        .expect("failed to open log stream");
+    let tracer_guard = config.log.otlp.as_deref().map(|endpoint| {
+        telemetry::init_tracer(endpoint).expect("failed to initialize otlp exporter")
+    });
+
     tracing_subscriber::registry()
-        .with(match EnvFilter::try_from_default_env() {
-            Ok(filter) => filter.boxed(),
-            Err(_) => Targets::new()
-                .with_target(env!("CARGO_CRATE_NAME"), config.log.level.try_as_level())
-                .boxed(),
-        })
         .with(
             tracing_subscriber::fmt::layer()
                 .with_writer(writer)
-                .event_format(config.log.format.to_format()),
+                .event_format(config.log.format.to_format())
+                .with_filter(match EnvFilter::try_from_default_env() {
+                    Ok(filter) => FilterExt::boxed(filter),
+                    Err(_) => FilterExt::boxed(
+                        Targets::new()
+                            .with_target(env!("CARGO_CRATE_NAME"), config.log.level.try_as_level()),
+                    ),
+                }),
         )
+        .with(tracer_guard.as_ref().map(|tracer_guard| {
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer_guard.tracer())
+                .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::INFO))
+        }))
         .init();
 
-    guard
+    (guard, tracer_guard)
 }
 
-async fn init_db(config: &AppConfig) -> DbConn {
+async fn init_db(config: &AppConfig, migrate: bool) -> DbConn {
     let mut options =
         ConnectOptions::new(config.database.try_to_url().expect("invalid database url"));
     options.sqlx_logging_level(config.database.log_level.as_filter());
@@ -271,13 +632,271 @@ async fn init_db(config: &AppConfig) -> DbConn {
     let connection = Database::connect(options)
         .await
         .expect("failed to connect to database");
-    Migrator::up(&connection, None)
-        .await
-        .expect("failed to run migrations");
+    if migrate {
+        Migrator::up(&connection, None)
+            .await
+            .expect("failed to run migrations");
+    }
 
     connection
 }
 
+fn init_chunk_storage(config: &AppConfig) -> ChunkStorage {
+    let chunk_storage = ChunkStorage::new(config.tier.path.clone());
+    if config.tier.paths.is_empty() {
+        chunk_storage
+    } else {
+        chunk_storage
+            .with_erasure(
+                config.tier.paths.clone(),
+                config.tier.data_shards,
+                config.tier.parity_shards,
+            )
+            .expect("invalid erasure tier")
+    }
+}
+
+async fn sweep_staged_chunks(db_conn: DbConn, ttl: Duration) {
+    let mut interval = tokio::time::interval(ttl);
+    loop {
+        interval.tick().await;
+
+        match StagingMutation::delete_many_expired(&db_conn, (SystemTime::now() - ttl).into()).await
+        {
+            Ok(res) => debug!(rows_affected = res.rows_affected, "swept staging sessions"),
+            Err(err) => error!(%err, "DatabaseError"),
+        }
+    }
+}
+
+async fn move_cold_versions(
+    db_conn: DbConn,
+    chunk_storage: Arc<ChunkStorage>,
+    config: Arc<AppConfig>,
+    target: ChunkTier,
+) {
+    let tier = &config.tier;
+    let period = Duration::from_secs(tier.interval);
+
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        let ids = match VersionQuery::find_many_ids_tierable(
+            &db_conn,
+            target,
+            tier.min_age
+                .map(|min_age| (SystemTime::now() - Duration::from_secs(min_age)).into()),
+            tier.min_size.map(|min_size| min_size.as_u64()),
+            tier.noncurrent,
+            &tier.storage_classes,
+            tier.batch_size,
+        )
+        .await
+        {
+            Ok(ids) => ids,
+            Err(err) => {
+                error!(%err, "DatabaseError");
+                continue;
+            }
+        };
+        for id in ids {
+            match ChunkMutation::update_many_tier_by_version_id(
+                &db_conn,
+                &chunk_storage,
+                id,
+                target,
+            )
+            .await
+            {
+                Ok(rows_affected) => debug!(%id, rows_affected, "moved version chunks"),
+                Err(err) => error!(%id, %err, "ChunkTierError"),
+            }
+        }
+
+        for tier in chunk_storage.tiers() {
+            match ChunkMutation::delete_many_orphaned(
+                &db_conn,
+                &chunk_storage,
+                tier,
+                SystemTime::now() - period,
+            )
+            .await
+            {
+                Ok(rows_affected) => debug!(?tier, rows_affected, "swept orphaned chunks"),
+                Err(err) => error!(?tier, %err, "ChunkTierError"),
+            }
+        }
+
+        let ids = chunk_storage.take_degraded();
+        if !ids.is_empty()
+            && let Err(err) = ChunkMutation::update_many_degraded(&db_conn, ids.clone()).await
+        {
+            error!(%err, "DatabaseError");
+            chunk_storage.extend_degraded(ids);
+        }
+        match ChunkMutation::repair_many_degraded(&db_conn, &chunk_storage, tier.batch_size).await {
+            Ok(rows_affected) => debug!(rows_affected, "repaired degraded chunks"),
+            Err(err) => error!(%err, "ChunkTierError"),
+        }
+        match ChunkQuery::find_many_degraded_version_ids(&db_conn).await {
+            Ok(degraded) if !degraded.is_empty() => warn!(?degraded, "degraded versions"),
+            Ok(_) => {}
+            Err(err) => error!(%err, "DatabaseError"),
+        }
+    }
+}
+
+async fn scrub_versions(
+    db_conn: DbConn,
+    chunk_storage: Arc<ChunkStorage>,
+    config: Arc<AppConfig>,
+    scrub_lock: Arc<Mutex<()>>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.scrub.interval));
+    loop {
+        interval.tick().await;
+
+        let _guard = scrub_lock.lock().await;
+        scrub_all_versions(&db_conn, &chunk_storage, &config).await;
+    }
+}
+
+async fn scrub_all_versions(db_conn: &DbConn, chunk_storage: &ChunkStorage, config: &AppConfig) {
+    let scrub = &config.scrub;
+
+    let mut after = None;
+    loop {
+        let versions = match VersionQuery::find_many_after(db_conn, after, scrub.batch_size).await {
+            Ok(versions) => versions,
+            Err(err) => {
+                error!(%err, "DatabaseError");
+                break;
+            }
+        };
+        let Some(last) = versions.last() else {
+            break;
+        };
+        after = Some(last.id);
+
+        for version in versions {
+            match CorruptVersionMutation::scrub(db_conn, chunk_storage, &version, scrub.rate).await
+            {
+                Ok(Some(corrupt_version)) => {
+                    warn!(id = %version.id, reason = corrupt_version.reason, "corrupt version");
+                }
+                Ok(None) => debug!(id = %version.id, "scrubbed version"),
+                Err(err) => error!(id = %version.id, %err, "ScrubError"),
+            }
+        }
+    }
+}
+
+async fn collect_garbage(
+    db_conn: &DbConn,
+    chunk_storage: &ChunkStorage,
+    before: SystemTime,
+) -> InsRes<(u64, u64)> {
+    let staged = StagingMutation::delete_many_expired(db_conn, before.into())
+        .await?
+        .rows_affected;
+
+    let mut orphaned = 0;
+    for tier in chunk_storage.tiers() {
+        orphaned +=
+            ChunkMutation::delete_many_orphaned(db_conn, chunk_storage, tier, before).await?;
+    }
+
+    Ok((staged, orphaned))
+}
+
+async fn deliver_access_logs(
+    db_conn: DbConn,
+    config: Arc<AppConfig>,
+    delivery: LogDelivery,
+    period: Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        match access_log::deliver(&db_conn, &config, &delivery).await {
+            Ok(delivered) => debug!(delivered, "delivered access logs"),
+            Err(err) => error!(%err, "DeliveryError"),
+        }
+    }
+}
+
+async fn scan_mounted_bucket(db_conn: DbConn, bucket: String, path: PathBuf, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        match mount::scan(&db_conn, &bucket, &path).await {
+            Ok((updated, deleted)) => debug!(bucket, updated, deleted, "scanned mounted bucket"),
+            Err(err) => error!(bucket, %err, "ScanError"),
+        }
+    }
+}
+
+async fn reload_tls_certificate(
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    config: Arc<AppConfig>,
+    period: Duration,
+) {
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .expect("failed to install signal handler");
+
+    let mut interval = tokio::time::interval(period);
+    let mut modified = tls::find_modified(&config);
+    loop {
+        #[cfg(unix)]
+        let hangup = hangup.recv();
+
+        #[cfg(not(unix))]
+        let hangup = future::pending::<Option<()>>();
+
+        tokio::select! {
+            _ = interval.tick() => {
+                let current = tls::find_modified(&config);
+                if current == modified {
+                    continue;
+                }
+                modified = current;
+            }
+            _ = hangup => {}
+        }
+
+        match tls::load_acceptor(&config) {
+            Ok(Some(new_acceptor)) => {
+                *acceptor.write() = new_acceptor;
+                info!("reloaded tls certificate");
+            }
+            Ok(None) => {}
+            Err(err) => error!(%err, "TlsError"),
+        }
+    }
+}
+
+async fn log_chunk_cache_stats(chunk_cache: Arc<ChunkCache>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        let stats = chunk_cache.stats();
+        #[cfg(debug_assertions)]
+        debug!(
+            ?stats,
+            allocated = %bytesize::ByteSize::b(ALLOCATOR.allocated() as u64),
+            limit = %bytesize::ByteSize::b(ALLOCATOR.limit() as u64),
+            "chunk cache stats"
+        );
+        #[cfg(not(debug_assertions))]
+        debug!(?stats, "chunk cache stats");
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -302,6 +921,17 @@ async fn shutdown_signal() {
     }
 }
 
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> io::Result<UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+
+    UnixListener::bind(path)
+}
+
 async fn set_process_time(request: Request, next: Next) -> Response {
     let start_time = Instant::now();
     let mut response = next.run(request).await;
@@ -325,10 +955,25 @@ async fn set_process_time(request: Request, next: Next) -> Response {
 async fn handle_app_err(common: CommonExtInput, request: Request, next: Next) -> Response {
     let mut response = next.run(request).await;
 
-    response
-        .extensions_mut()
-        .remove::<AppErrorDiscriminants>()
-        .map_or(response, |err| err.into_response(common))
+    match response
+        .extensions()
+        .get::<AppErrorDiscriminants>()
+        .copied()
+    {
+        Some(err) => {
+            let mut err_response = err.into_response(common);
+            err_response
+                .extensions_mut()
+                .extend(mem::take(response.extensions_mut()));
+            if let Some(retry_after) = response.headers_mut().remove(header::RETRY_AFTER) {
+                err_response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_after);
+            }
+            err_response
+        }
+        None => response,
+    }
 }
 
 //noinspection RsBorrowChecker
@@ -374,28 +1019,120 @@ async fn manage_db_txn(
     next: Next,
 ) -> AppResult<Response> {
     let db_txn = Arc::new(db_conn.begin().await?);
+    metrics::OPEN_TRANSACTIONS.inc();
     request.extensions_mut().insert(Arc::clone(&db_txn));
     let response = next.run(request).await;
 
     let db_txn = Arc::into_inner(db_txn).expect("failed to take transaction");
-    if response.status().is_success() {
+    let result = if response.status().is_success() {
         debug!("committing transaction");
-        db_txn.commit().await?;
+        db_txn.commit().await
     } else {
         debug!("rolling back transaction");
-        db_txn.rollback().await?;
+        db_txn.rollback().await
+    };
+    metrics::OPEN_TRANSACTIONS.dec();
+    result?;
+
+    Ok(response)
+}
+
+fn validate_preconditions(
+    version: Option<&version::Model>,
+    if_match: Option<&str>,
+    if_none_match: Option<&str>,
+    if_modified_since: Option<SystemTime>,
+    if_unmodified_since: Option<SystemTime>,
+) -> AppResult<()> {
+    let matches = |e_tag: &str, value: &str| {
+        value == "*" || value.trim_matches('"') == e_tag.trim_matches('"')
+    };
+
+    if let Some(if_match) = if_match {
+        let version = version.ok_or(AppError::NoSuchKey)?;
+        if !matches(&version.e_tag(), if_match) {
+            Err(AppError::PreconditionFailed)?;
+        }
+    }
+    if let Some(version) = version {
+        if if_none_match.is_some_and(|if_none_match| matches(&version.e_tag(), if_none_match)) {
+            Err(AppError::PreconditionFailed)?;
+        }
+
+        let last_modified = version.last_modified().timestamp();
+        let timestamp = |time| DateTimeUtc::from(time).timestamp();
+        if if_modified_since.is_some_and(|time| last_modified <= timestamp(time))
+            || if_unmodified_since.is_some_and(|time| last_modified > timestamp(time))
+        {
+            Err(AppError::PreconditionFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_replaced_usage(
+    db: &impl ConnectionTrait,
+    bucket: &bucket::Model,
+    key: &str,
+) -> AppResult<(i64, i64)> {
+    if bucket.versioning.unwrap_or_default() {
+        return Ok((0, 0));
     }
 
+    Ok(ObjectQuery::find_both_latest_version(db, bucket.id, key)
+        .await?
+        .map(|(_, version)| version)
+        .filter(|version| !version.versioning && version.parts_count.is_some())
+        .map_or((0, 0), |version| (version.size() as i64, 1)))
+}
+
+async fn ensure_quota(
+    db: &impl ConnectionTrait,
+    bucket_id: Uuid,
+    owner_id: Uuid,
+    size: i64,
+    objects: i64,
+) -> AppResult<()> {
+    // usage is applied by deferred triggers at commit, so the rows stay locked until then
+    let bucket = BucketQuery::find_for_update(db, bucket_id)
+        .await?
+        .ok_or(AppError::NoSuchBucket)?;
+    let owner = OwnerQuery::find_for_update(db, owner_id)
+        .await?
+        .ok_or(AppError::AccessDenied)?;
+    if bucket.exceeds_quota(size, objects) || owner.exceeds_quota(size, objects) {
+        Err(AppError::QuotaExceeded)?;
+    }
+
+    Ok(())
+}
+
+async fn resolve_owner(
+    client_owner: Option<Extension<ConnectInfo<ClientOwner>>>,
+    Extension(db): Extension<DbTxn>,
+    mut request: Request,
+    next: Next,
+) -> AppResult<Response> {
+    let name = client_owner.and_then(|Extension(ConnectInfo(client_owner))| client_owner.name);
+    let owner = OwnerQuery::find(&*db, name.as_deref().unwrap_or("minil"))
+        .await?
+        .filter(|owner| !owner.disabled)
+        .ok_or(AppError::AccessDenied)?;
+    request.extensions_mut().insert(owner.clone());
+
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(owner);
+
     Ok(response)
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_buckets(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListBucketsInput,
 ) -> AppResult<ListBucketsOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     let limit = input.query.max_buckets + 1;
     let mut buckets = BucketQuery::find_many(
         &*db,
@@ -439,13 +1176,12 @@ async fn list_buckets(
         .build())
 }
 
-#[instrument(skip(db), ret)] // todo silence err
+#[instrument(skip(owner, db), ret)] // todo silence err
 async fn delete_bucket_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: DeleteBucketTaggingInput,
 ) -> AppResult<DeleteBucketTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
@@ -457,13 +1193,12 @@ async fn delete_bucket_tagging(
     Ok(DeleteBucketTaggingOutput::builder().build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn delete_bucket(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: DeleteBucketInput,
 ) -> AppResult<DeleteBucketOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     BucketMutation::delete(&*db, owner.id, &input.path.bucket)
         .await?
@@ -472,13 +1207,12 @@ async fn delete_bucket(
     Ok(DeleteBucketOutput::builder().build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_objects_v2(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListObjectsV2Input,
 ) -> AppResult<ListObjectsV2Output> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_matches!(input.header.optional_object_attributes, None);
     app_ensure_eq!(input.header.request_payer, None);
 
@@ -564,6 +1298,14 @@ async fn list_objects_v2(
                                     },
                                 ))
                                 .size(version.size())
+                                .maybe_storage_class(
+                                    version
+                                        .storage_class
+                                        .as_deref()
+                                        .unwrap_or("STANDARD")
+                                        .parse()
+                                        .ok(),
+                                )
                                 .build()
                         })
                         .collect(),
@@ -583,13 +1325,42 @@ async fn list_objects_v2(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
+async fn get_bucket_logging(
+    Extension(owner): Extension<owner::Model>,
+    Extension(db): Extension<DbTxn>,
+    input: GetBucketLoggingInput,
+) -> AppResult<GetBucketLoggingOutput> {
+    app_validate_owner!(input.header.expected_bucket_owner, owner.name);
+    let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
+        .await?
+        .ok_or(AppError::NoSuchBucket)?;
+    let logging_enabled =
+        bucket
+            .logging_target
+            .zip(bucket.logging_prefix)
+            .map(|(target_bucket, target_prefix)| {
+                LoggingEnabled::builder()
+                    .target_bucket(target_bucket)
+                    .target_prefix(target_prefix)
+                    .build()
+            });
+
+    Ok(GetBucketLoggingOutput::builder()
+        .body(
+            GetBucketLoggingOutputBody::builder()
+                .maybe_logging_enabled(logging_enabled)
+                .build(),
+        )
+        .build())
+}
+
+#[instrument(skip(owner, db), ret)]
 async fn get_bucket_location(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: GetBucketLocationInput,
 ) -> AppResult<GetBucketLocationOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
@@ -604,13 +1375,12 @@ async fn get_bucket_location(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn get_bucket_versioning(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: GetBucketVersioningInput,
 ) -> AppResult<GetBucketVersioningOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
@@ -640,13 +1410,12 @@ async fn get_bucket_versioning(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_object_versions(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListObjectVersionsInput,
 ) -> AppResult<ListObjectVersionsOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_matches!(input.header.optional_object_attributes, None);
     app_ensure_eq!(input.header.request_payer, None);
 
@@ -777,13 +1546,12 @@ async fn list_object_versions(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn get_bucket_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: GetBucketTaggingInput,
 ) -> AppResult<GetBucketTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     let tag_set = BucketQuery::find_also_tag_set(&*db, owner.id, &input.path.bucket)
         .await?
@@ -809,13 +1577,12 @@ async fn get_bucket_tagging(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_multipart_uploads(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListMultipartUploadsInput,
 ) -> AppResult<ListMultipartUploadsOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.request_payer, None);
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
@@ -903,6 +1670,14 @@ async fn list_multipart_uploads(
                                 .upload_id(upload.id)
                                 .key(upload.key)
                                 .initiated(upload.created_at)
+                                .maybe_storage_class(
+                                    upload
+                                        .storage_class
+                                        .as_deref()
+                                        .unwrap_or("STANDARD")
+                                        .parse()
+                                        .ok(),
+                                )
                                 .owner(
                                     Owner::builder()
                                         .display_name(owner.name.clone())
@@ -925,13 +1700,12 @@ async fn list_multipart_uploads(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_objects(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListObjectsInput,
 ) -> AppResult<ListObjectsOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_matches!(input.header.optional_object_attributes, None);
     app_ensure_eq!(input.header.request_payer, None);
 
@@ -1010,6 +1784,14 @@ async fn list_objects(
                                         .build(),
                                 )
                                 .size(version.size())
+                                .maybe_storage_class(
+                                    version
+                                        .storage_class
+                                        .as_deref()
+                                        .unwrap_or("STANDARD")
+                                        .parse()
+                                        .ok(),
+                                )
                                 .build()
                         })
                         .collect(),
@@ -1027,34 +1809,45 @@ async fn list_objects(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn head_bucket(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: HeadBucketInput,
-) -> AppResult<HeadBucketOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
+) -> AppResult<(HeaderMap, HeadBucketOutput)> {
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
-    BucketQuery::find(&*db, owner.id, &input.path.bucket)
+    let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
         .ok_or(AppError::NoSuchBucket)?;
 
-    Ok(HeadBucketOutput::builder()
-        .header(
-            HeadBucketOutputHeader::builder()
-                .bucket_region(NODE_REGION.to_owned())
-                .build(),
-        )
-        .build())
+    let mut headers = HeaderMap::new();
+    headers.insert(USAGE_SIZE_HEADER, bucket.usage_size.into());
+    headers.insert(USAGE_OBJECTS_HEADER, bucket.usage_objects.into());
+    if let Some(quota_size) = bucket.quota_size {
+        headers.insert(QUOTA_SIZE_HEADER, quota_size.into());
+    }
+    if let Some(quota_objects) = bucket.quota_objects {
+        headers.insert(QUOTA_OBJECTS_HEADER, quota_objects.into());
+    }
+
+    Ok((
+        headers,
+        HeadBucketOutput::builder()
+            .header(
+                HeadBucketOutputHeader::builder()
+                    .bucket_region(NODE_REGION.to_owned())
+                    .build(),
+            )
+            .build(),
+    ))
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn put_bucket_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: PutBucketTaggingInput,
 ) -> AppResult<PutBucketTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.sdk_checksum_algorithm, None);
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
@@ -1091,13 +1884,38 @@ async fn put_bucket_tagging(
     Ok(PutBucketTaggingOutput::builder().build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
+async fn put_bucket_logging(
+    Extension(owner): Extension<owner::Model>,
+    Extension(db): Extension<DbTxn>,
+    input: PutBucketLoggingInput,
+) -> AppResult<PutBucketLoggingOutput> {
+    app_ensure_eq!(input.header.sdk_checksum_algorithm, None);
+
+    app_validate_owner!(input.header.expected_bucket_owner, owner.name);
+    let target = match input.body.logging_enabled {
+        Some(logging_enabled) => {
+            BucketQuery::find(&*db, owner.id, &logging_enabled.target_bucket)
+                .await?
+                .ok_or(AppError::InvalidTargetBucketForLogging)?;
+
+            Some((logging_enabled.target_bucket, logging_enabled.target_prefix))
+        }
+        None => None,
+    };
+    BucketMutation::update_logging(&*db, owner.id, &input.path.bucket, target)
+        .await?
+        .ok_or(AppError::NoSuchBucket)?;
+
+    Ok(PutBucketLoggingOutput::builder().build())
+}
+
+#[instrument(skip(owner, db), ret)]
 async fn put_bucket_versioning(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: PutBucketVersioningInput,
 ) -> AppResult<PutBucketVersioningOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.mfa, None);
     app_ensure_eq!(input.header.sdk_checksum_algorithm, None);
     app_ensure_matches!(
@@ -1121,13 +1939,12 @@ async fn put_bucket_versioning(
     Ok(PutBucketVersioningOutput::builder().build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn create_bucket(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: CreateBucketInput,
 ) -> AppResult<CreateBucketOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_matches!(input.header.acl, None);
     app_ensure_matches!(input.header.bucket_object_lock_enabled, None | Some(false));
     app_ensure_eq!(input.header.grant_full_control, None);
@@ -1182,13 +1999,12 @@ async fn create_bucket(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn delete_object_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: DeleteObjectTaggingInput,
 ) -> AppResult<DeleteObjectTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
@@ -1221,13 +2037,12 @@ async fn delete_object_tagging(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn abort_multipart_upload(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: AbortMultipartUploadInput,
 ) -> AppResult<AbortMultipartUploadOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.if_match_initiated_time, None);
     app_ensure_eq!(input.header.request_payer, None);
 
@@ -1244,13 +2059,13 @@ async fn abort_multipart_upload(
         .build())
 }
 
-#[instrument(skip(db), ret)] // todo silence err
+#[instrument(skip(chunk_cache, owner, db), ret)] // todo silence err
 async fn delete_object(
+    State(chunk_cache): State<Arc<ChunkCache>>,
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: DeleteObjectInput,
 ) -> AppResult<DeleteObjectOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.if_match, None);
     app_ensure_eq!(input.header.bypass_governance_retention, None);
     app_ensure_eq!(input.header.if_match_last_modified_time, None);
@@ -1264,7 +2079,7 @@ async fn delete_object(
         .ok_or(AppError::NoSuchBucket)?;
     let (delete_marker, version_id) = match (input.query.version_id, bucket.versioning) {
         (Some(version_id), Some(_)) => {
-            let delete_marker = ObjectMutation::delete_also_version_nullable(
+            let version = ObjectMutation::delete_also_version_nullable(
                 &*db,
                 bucket.id,
                 &input.path.key,
@@ -1273,11 +2088,10 @@ async fn delete_object(
             .await?
             .ok_or(AppError::NoSuchKey)?
             .1
-            .ok_or(AppError::NoSuchVersion)?
-            .parts_count
-            .is_none();
+            .ok_or(AppError::NoSuchVersion)?;
+            chunk_cache.invalidate_version(version.id);
 
-            (delete_marker, None)
+            (version.parts_count.is_none(), None)
         }
         (None, Some(versioning)) => {
             let version = if cfg!(feature = "create-delete") {
@@ -1300,6 +2114,7 @@ async fn delete_object(
                 .ok_or(AppError::NoSuchKey)?
                 .1
             };
+            chunk_cache.invalidate_version(version.id);
 
             (true, Some(version.id()))
         }
@@ -1307,6 +2122,7 @@ async fn delete_object(
             let object = ObjectMutation::delete(&*db, bucket.id, &input.path.key)
                 .await?
                 .ok_or(AppError::NoSuchKey)?;
+            chunk_cache.invalidate_version(object.version_id);
 
             if let Some(version_id) = version_id {
                 if !version_id.is_nil() && version_id != object.version_id {
@@ -1328,13 +2144,12 @@ async fn delete_object(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn get_object_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: GetObjectTaggingInput,
 ) -> AppResult<GetObjectTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.request_payer, None);
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
@@ -1382,13 +2197,12 @@ async fn get_object_tagging(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn list_parts(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: ListPartsInput,
 ) -> AppResult<ListPartsOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.request_payer, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_algorithm, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_key, None);
@@ -1455,20 +2269,30 @@ async fn list_parts(
                         .collect(),
                 )
                 .maybe_part_number_marker(input.query.part_number_marker)
+                .maybe_storage_class(
+                    upload
+                        .storage_class
+                        .as_deref()
+                        .unwrap_or("STANDARD")
+                        .parse()
+                        .ok(),
+                )
                 .upload_id(upload.id)
                 .build(),
         )
         .build())
 }
 
-#[instrument(skip(db_conn, db), ret)]
+#[instrument(skip(config, db_conn, chunk_cache, chunk_storage, owner, db), ret)]
 async fn get_object(
+    State(config): State<Arc<AppConfig>>,
     State(db_conn): State<DbConn>,
+    State(chunk_cache): State<Arc<ChunkCache>>,
+    State(chunk_storage): State<Arc<ChunkStorage>>,
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: GetObjectInput,
 ) -> AppResult<GetObjectOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.if_match, None);
     app_ensure_eq!(input.header.if_modified_since, None);
     app_ensure_eq!(input.header.if_none_match, None);
@@ -1523,7 +2347,23 @@ async fn get_object(
             .body(Body::empty())
             .build());
     }
+    if config.scrub.reject && CorruptVersionQuery::exists(&*db, version.id).await? {
+        #[allow(deprecated)]
+        Err(AppError::InternalError)?;
+    }
     let (part_id, size, e_tag, last_modified) = match input.query.part_number {
+        Some(part_number) if version.data.is_some() || version.path.is_some() => {
+            if part_number != 1 {
+                Err(AppError::InvalidPart)?;
+            }
+
+            (
+                None,
+                version.size(),
+                version.e_tag(),
+                version.last_modified(),
+            )
+        }
         Some(part_number) => {
             if cfg!(not(feature = "ranged-part")) && input.header.range.is_some() {
                 Err(AppError::InternalError)?;
@@ -1557,19 +2397,33 @@ async fn get_object(
                 .map_err(|_| AppError::InvalidRange)
         })
         .transpose()?;
-    let body = match part_id {
-        Some(part_id) => ChunkQuery::find_many_ranged_part_data_by_version_part_id(
-            db_conn,
-            part_id,
-            range.clone(),
-        )
-        .left_stream(),
-        None => ChunkQuery::find_many_ranged_version_data_by_version_id(
-            db_conn,
-            version.id,
-            range.clone(),
-        )
-        .right_stream(),
+    let body = match (version.data.as_deref(), version.path.clone(), part_id) {
+        (Some(data), _, _) => Body::from(match &range {
+            Some(range) => data[*range.start() as usize..=*range.end() as usize].to_vec(),
+            None => data.to_vec(),
+        }),
+        (None, Some(path), _) => {
+            Body::from_stream(MountQuery::find_ranged_data(path, size, range.clone()))
+        }
+        (None, None, Some(part_id)) => {
+            Body::from_stream(ChunkQuery::find_many_ranged_part_data_by_version_part_id(
+                db_conn,
+                chunk_cache,
+                chunk_storage,
+                version.id,
+                part_id,
+                range.clone(),
+            ))
+        }
+        (None, None, None) => {
+            Body::from_stream(ChunkQuery::find_many_ranged_version_data_by_version_id(
+                db_conn,
+                chunk_cache,
+                chunk_storage,
+                version.id,
+                range.clone(),
+            ))
+        }
     };
 
     Ok(GetObjectOutput::builder()
@@ -1590,25 +2444,35 @@ async fn get_object(
                     last_byte: *range.end(),
                     complete_length: size,
                 }))
-                .maybe_content_type(input.query.response_content_type)
+                .maybe_content_type(
+                    input
+                        .query
+                        .response_content_type
+                        .or_else(|| version.mime.as_deref().and_then(|mime| mime.parse().ok())),
+                )
                 .e_tag(e_tag)
                 .maybe_expires(input.query.response_expires)
                 .last_modified(SystemTime::from(last_modified))
                 .maybe_mp_parts_count(version.mp_parts_count())
+                .maybe_storage_class(
+                    version
+                        .storage_class
+                        .as_deref()
+                        .and_then(|storage_class| storage_class.parse().ok()),
+                )
                 .maybe_version_id(bucket.versioning.map(|_| version.id()))
                 .build(),
         )
-        .body(Body::from_stream(body))
+        .body(body)
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn head_object(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: HeadObjectInput,
 ) -> AppResult<HeadObjectOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.if_match, None);
     app_ensure_eq!(input.header.if_modified_since, None);
     app_ensure_eq!(input.header.if_none_match, None);
@@ -1663,6 +2527,13 @@ async fn head_object(
             .build());
     }
     let (size, e_tag, last_modified) = match input.query.part_number {
+        Some(part_number) if version.data.is_some() || version.path.is_some() => {
+            if part_number != 1 {
+                Err(AppError::InvalidPart)?;
+            }
+
+            (version.size(), version.e_tag(), version.last_modified())
+        }
         Some(part_number) => {
             if cfg!(not(feature = "ranged-part")) && input.header.range.is_some() {
                 Err(AppError::InternalError)?;
@@ -1705,24 +2576,34 @@ async fn head_object(
                     last_byte: *range.end(),
                     complete_length: size,
                 }))
-                .maybe_content_type(input.query.response_content_type)
+                .maybe_content_type(
+                    input
+                        .query
+                        .response_content_type
+                        .or_else(|| version.mime.as_deref().and_then(|mime| mime.parse().ok())),
+                )
                 .e_tag(e_tag)
                 .maybe_expires(input.query.response_expires)
                 .last_modified(SystemTime::from(last_modified))
                 .maybe_mp_parts_count(version.mp_parts_count())
+                .maybe_storage_class(
+                    version
+                        .storage_class
+                        .as_deref()
+                        .and_then(|storage_class| storage_class.parse().ok()),
+                )
                 .maybe_version_id(bucket.versioning.map(|_| version.id()))
                 .build(),
         )
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn create_multipart_upload(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: CreateMultipartUploadInput,
 ) -> AppResult<CreateMultipartUploadOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.cache_control, None);
     app_ensure_eq!(input.header.content_disposition, None);
     app_ensure_eq!(input.header.content_encoding, None);
@@ -1746,7 +2627,6 @@ async fn create_multipart_upload(
     app_ensure_eq!(input.header.server_side_encryption_customer_algorithm, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_key, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_key_md5, None);
-    app_ensure_matches!(input.header.storage_class, None);
     app_ensure_eq!(input.header.website_redirect_location, None);
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
@@ -1758,6 +2638,12 @@ async fn create_multipart_upload(
         bucket.id,
         input.path.key.clone(),
         input.header.content_type.as_ref(),
+        input
+            .header
+            .storage_class
+            .as_ref()
+            .filter(|storage_class| **storage_class != StorageClass::Standard)
+            .map(<&str>::from),
     )
     .await?;
     if let Some(tagging) = input.header.tagging {
@@ -1790,13 +2676,13 @@ async fn create_multipart_upload(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(chunk_cache, owner, db), ret)]
 async fn complete_multipart_upload(
+    State(chunk_cache): State<Arc<ChunkCache>>,
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: CompleteMultipartUploadInput,
 ) -> AppResult<CompleteMultipartUploadOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.if_match, None);
     app_ensure_eq!(input.header.if_none_match, None);
     app_ensure_eq!(input.header.checksum_crc32, None);
@@ -1863,6 +2749,7 @@ async fn complete_multipart_upload(
         .into_iter()
         .collect::<Option<Vec<_>>>()
         .ok_or(AppError::InvalidPart)?;
+    let (replaced_size, replaced_objects) = find_replaced_usage(&*db, &bucket, &upload.key).await?;
     let (object, version) = ObjectMutation::upsert_also_version_from_parts(
         &*db,
         bucket.id,
@@ -1872,9 +2759,19 @@ async fn complete_multipart_upload(
             .mime
             .map(|mime| mime.parse::<Mime>().unwrap())
             .as_ref(),
+        upload.storage_class.as_deref(),
         parts.into_iter(),
     )
     .await?;
+    ensure_quota(
+        &*db,
+        bucket.id,
+        owner.id,
+        version.size() as i64 - replaced_size,
+        1 - replaced_objects,
+    )
+    .await?;
+    chunk_cache.invalidate_version(version.id);
     UploadMutation::delete(&*db, upload.id, bucket.id, &object.key)
         .await?
         .ok_or(AppError::NoSuchUpload)?;
@@ -1895,13 +2792,12 @@ async fn complete_multipart_upload(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(owner, db), ret)]
 async fn put_object_tagging(
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: PutObjectTaggingInput,
 ) -> AppResult<PutObjectTaggingOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.request_payer, None);
     app_ensure_eq!(input.header.sdk_checksum_algorithm, None);
 
@@ -1963,13 +2859,93 @@ async fn put_object_tagging(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(chunk_cache, owner, db), ret)]
+async fn rename_object(
+    State(chunk_cache): State<Arc<ChunkCache>>,
+    Extension(owner): Extension<owner::Model>,
+    Extension(db): Extension<DbTxn>,
+    input: RenameObjectInput,
+) -> AppResult<RenameObjectOutput> {
+    let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
+        .await?
+        .ok_or(AppError::NoSuchBucket)?;
+    let rename_source =
+        urlencoding::decode(&input.header.rename_source).map_err(|_| AppError::InvalidArgument)?;
+    let (source_bucket, source_key) = rename_source
+        .trim_start_matches('/')
+        .split_once('/')
+        .ok_or(AppError::InvalidArgument)?;
+    app_ensure_eq!(source_bucket, bucket.name);
+
+    let source = ObjectQuery::find_both_latest_version_for_update(&*db, bucket.id, source_key);
+    let destination =
+        ObjectQuery::find_both_latest_version_for_update(&*db, bucket.id, &input.path.key);
+    let (source, destination) = if source_key < input.path.key.as_str() {
+        let source = source.await?;
+        (source, destination.await?)
+    } else {
+        let destination = destination.await?;
+        (source.await?, destination)
+    };
+    let (_, version) = source
+        .filter(|(_, version)| version.parts_count.is_some())
+        .ok_or(AppError::NoSuchKey)?;
+    validate_preconditions(
+        Some(&version),
+        input.header.rename_source_if_match.as_deref(),
+        input.header.rename_source_if_none_match.as_deref(),
+        input
+            .header
+            .rename_source_if_modified_since
+            .map(SystemTime::from),
+        input
+            .header
+            .rename_source_if_unmodified_since
+            .map(SystemTime::from),
+    )?;
+    validate_preconditions(
+        destination
+            .as_ref()
+            .map(|(_, version)| version)
+            .filter(|version| version.parts_count.is_some()),
+        input.header.if_match.as_deref(),
+        input.header.if_none_match.as_deref(),
+        input.header.if_modified_since.map(SystemTime::from),
+        input.header.if_unmodified_since.map(SystemTime::from),
+    )?;
+    if source_key == input.path.key {
+        return Ok(RenameObjectOutput::builder().build());
+    }
+
+    // the rename moves the object row, so in versioned buckets the whole version history of the
+    // source moves with it; an existing destination history is never merged or overwritten
+    if let Some((_, version)) = destination {
+        if bucket.versioning.is_some() {
+            Err(AppError::OperationAborted)?;
+        }
+
+        ObjectMutation::delete(&*db, bucket.id, &input.path.key).await?;
+        chunk_cache.invalidate_version(version.id);
+    }
+    ObjectMutation::update_key(&*db, bucket.id, source_key, input.path.key)
+        .await
+        .map_err(|err| match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => AppError::ConditionalRequestConflict,
+            _ => err.into(),
+        })?
+        .ok_or(AppError::NoSuchKey)?;
+
+    Ok(RenameObjectOutput::builder().build())
+}
+
+#[instrument(skip(config, db_conn, owner, db), ret)]
 async fn upload_part(
+    State(config): State<Arc<AppConfig>>,
+    State(db_conn): State<DbConn>,
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: UploadPartInput,
 ) -> AppResult<UploadPartOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.checksum_crc32, None);
     app_ensure_eq!(input.header.checksum_crc32c, None);
     app_ensure_eq!(input.header.checksum_crc64nvme, None);
@@ -1982,7 +2958,7 @@ async fn upload_part(
     app_ensure_eq!(input.header.server_side_encryption_customer_key_md5, None);
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
-    let upload = BucketQuery::find_also_upload(
+    let (bucket, upload) = BucketQuery::find_also_upload(
         &*db,
         owner.id,
         &input.path.bucket,
@@ -1990,16 +2966,26 @@ async fn upload_part(
         &input.path.key,
     )
     .await?
-    .ok_or(AppError::NoSuchBucket)?
-    .1
-    .ok_or(AppError::NoSuchUpload)?;
+    .ok_or(AppError::NoSuchBucket)?;
+    let upload = upload.ok_or(AppError::NoSuchUpload)?;
+    let uploaded =
+        UploadPartQuery::sum_size_except(&*db, upload.id, input.query.part_number).await?;
+    if let Some(content_length) = input.header.content_length {
+        let size = uploaded + content_length as i64;
+        if bucket.exceeds_quota(size, 0) || owner.exceeds_quota(size, 0) {
+            Err(AppError::QuotaExceeded)?;
+        }
+    }
     let part = UploadPartMutation::upsert_with_chunk(
         &*db,
+        &db_conn,
         upload.id,
         input.query.part_number,
+        config.database.chunk_size(),
         input.body.into_data_read(),
     )
     .await?;
+    ensure_quota(&*db, bucket.id, owner.id, uploaded + part.size, 0).await?;
 
     Ok(UploadPartOutput::builder()
         .header(
@@ -2010,13 +2996,15 @@ async fn upload_part(
         .build())
 }
 
-#[instrument(skip(db), ret)]
+#[instrument(skip(config, db_conn, chunk_cache, owner, db), ret)]
 async fn put_object(
+    State(config): State<Arc<AppConfig>>,
+    State(db_conn): State<DbConn>,
+    State(chunk_cache): State<Arc<ChunkCache>>,
+    Extension(owner): Extension<owner::Model>,
     Extension(db): Extension<DbTxn>,
     input: PutObjectInput,
 ) -> AppResult<PutObjectOutput> {
-    let owner = OwnerQuery::find(&*db, "minil").await?.unwrap();
-
     app_ensure_eq!(input.header.cache_control, None);
     app_ensure_eq!(input.header.content_disposition, None);
     app_ensure_eq!(input.header.content_encoding, None);
@@ -2046,23 +3034,94 @@ async fn put_object(
     app_ensure_eq!(input.header.server_side_encryption_customer_algorithm, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_key, None);
     app_ensure_eq!(input.header.server_side_encryption_customer_key_md5, None);
-    app_ensure_matches!(input.header.storage_class, None);
     app_ensure_eq!(input.header.website_redirect_location, None);
-    app_ensure_matches!(input.header.write_offset_bytes, None | Some(0));
 
     app_validate_owner!(input.header.expected_bucket_owner, owner.name);
     let bucket = BucketQuery::find(&*db, owner.id, &input.path.bucket)
         .await?
         .ok_or(AppError::NoSuchBucket)?;
-    let (_, version) = ObjectMutation::upsert_also_version(
-        &*db,
-        bucket.id,
-        input.path.key,
-        bucket.versioning.unwrap_or_default(),
-        input.header.content_type.as_ref(),
-        input.body.into_data_read(),
-    )
-    .await?;
+    let appendable = match input.header.write_offset_bytes {
+        Some(write_offset_bytes) => {
+            let version =
+                match ObjectQuery::find_both_latest_version(&*db, bucket.id, &input.path.key)
+                    .await?
+                {
+                    Some((_, version)) => VersionQuery::find_for_update(&*db, version.id)
+                        .await?
+                        .filter(|version| version.parts_count.is_some()),
+                    None => None,
+                };
+            if version.is_none() && write_offset_bytes != 0 {
+                Err(AppError::InvalidWriteOffset)?;
+            }
+
+            version.map(|version| (version, write_offset_bytes))
+        }
+        None => None,
+    };
+    let (replaced_size, replaced_objects) = if appendable.is_some() {
+        (0, 0)
+    } else {
+        find_replaced_usage(&*db, &bucket, &input.path.key).await?
+    };
+    if let Some(content_length) = input.header.content_length {
+        let (size, objects) = if appendable.is_some() {
+            (content_length as i64, 0)
+        } else {
+            (content_length as i64 - replaced_size, 1 - replaced_objects)
+        };
+        if bucket.exceeds_quota(size, objects) || owner.exceeds_quota(size, objects) {
+            Err(AppError::QuotaExceeded)?;
+        }
+    }
+    let (version, size, objects) = if let Some((version, offset)) = appendable {
+        // appends grow the version in place, which would rewrite history in a versioned bucket,
+        // and mounted versions are served from their file, which appended parts never reach
+        if version.versioning || version.path.is_some() {
+            Err(AppError::NotImplemented)?;
+        }
+        if version.mp_parts_count().unwrap_or(1) >= 10000 {
+            Err(AppError::TooManyParts)?;
+        }
+
+        let version = VersionMutation::append_also_part(
+            &*db,
+            &db_conn,
+            version,
+            offset,
+            config.database.chunk_size(),
+            input.body.into_data_read(),
+        )
+        .await?
+        .ok_or(AppError::InvalidWriteOffset)?;
+        let size = (version.size() - offset) as i64;
+
+        (version, size, 0)
+    } else {
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &*db,
+            &db_conn,
+            bucket.id,
+            input.path.key,
+            bucket.versioning.unwrap_or_default(),
+            input.header.content_type.as_ref(),
+            input
+                .header
+                .storage_class
+                .as_ref()
+                .filter(|storage_class| **storage_class != StorageClass::Standard)
+                .map(<&str>::from),
+            config.database.chunk_size(),
+            config.database.inline_threshold,
+            input.body.into_data_read(),
+        )
+        .await?;
+        let size = version.size() as i64 - replaced_size;
+
+        (version, size, 1 - replaced_objects)
+    };
+    ensure_quota(&*db, bucket.id, owner.id, size, objects).await?;
+    chunk_cache.invalidate_version(version.id);
     if let Some(tagging) = input.header.tagging {
         if tagging.len() > 10 {
             Err(AppError::InvalidTag)?;
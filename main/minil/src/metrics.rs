@@ -0,0 +1,314 @@
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::body::HttpBody;
+use axum::extract::Request;
+use axum::handler::Handler;
+use axum::middleware::Next;
+use axum::response::Response;
+use http_body_util::BodyExt as _;
+use minil_service::ChunkCache;
+use minil_service::ChunkCacheStats;
+use minil_service::prelude::*;
+use prometheus::Encoder as _;
+use prometheus::HistogramVec;
+use prometheus::IntCounter;
+use prometheus::IntCounterVec;
+use prometheus::IntGauge;
+use prometheus::IntGaugeVec;
+use prometheus::TextEncoder;
+use prometheus::register_histogram_vec;
+use prometheus::register_int_counter;
+use prometheus::register_int_counter_vec;
+use prometheus::register_int_gauge;
+use prometheus::register_int_gauge_vec;
+use sea_orm::DbConn;
+use sea_orm::DbErr;
+use tower::util::MapResponseLayer;
+
+use crate::error::AppErrorDiscriminants;
+
+const UNKNOWN_OPERATION: &str = "Unknown";
+
+static REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "minil_requests_total",
+        "Number of requests",
+        &["operation", "status"]
+    )
+    .expect("invalid metric")
+});
+static ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "minil_errors_total",
+        "Number of errors",
+        &["operation", "error"]
+    )
+    .expect("invalid metric")
+});
+static DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "minil_request_duration_seconds",
+        "Request duration until response headers",
+        &["operation"]
+    )
+    .expect("invalid metric")
+});
+static RECEIVED_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "minil_received_bytes_total",
+        "Number of request body bytes",
+        &["operation"]
+    )
+    .expect("invalid metric")
+});
+static SENT_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "minil_sent_bytes_total",
+        "Number of response body bytes",
+        &["operation"]
+    )
+    .expect("invalid metric")
+});
+pub(crate) static OPEN_TRANSACTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("minil_open_transactions", "Number of open transactions")
+        .expect("invalid metric")
+});
+static DB_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("minil_db_connections", "Number of pool connections")
+        .expect("invalid metric")
+});
+static DB_IDLE_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "minil_db_idle_connections",
+        "Number of idle pool connections"
+    )
+    .expect("invalid metric")
+});
+static BUCKET_SIZE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "minil_bucket_size_bytes",
+        "Stored bytes per bucket",
+        &["owner", "bucket"]
+    )
+    .expect("invalid metric")
+});
+static BUCKET_OBJECTS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "minil_bucket_objects",
+        "Stored objects per bucket",
+        &["owner", "bucket"]
+    )
+    .expect("invalid metric")
+});
+static CHUNK_CACHE_HITS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("minil_chunk_cache_hits_total", "Number of chunk cache hits")
+        .expect("invalid metric")
+});
+static CHUNK_CACHE_MISSES: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "minil_chunk_cache_misses_total",
+        "Number of chunk cache misses"
+    )
+    .expect("invalid metric")
+});
+static CHUNK_CACHE_EVICTIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "minil_chunk_cache_evictions_total",
+        "Number of chunk cache evictions"
+    )
+    .expect("invalid metric")
+});
+static CHUNK_CACHE_ENTRIES: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("minil_chunk_cache_entries", "Number of cached chunks")
+        .expect("invalid metric")
+});
+static CHUNK_CACHE_SIZE: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("minil_chunk_cache_size_bytes", "Cached chunk bytes")
+        .expect("invalid metric")
+});
+static CHUNK_CACHE_CAPACITY: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "minil_chunk_cache_capacity_bytes",
+        "Chunk cache capacity in bytes"
+    )
+    .expect("invalid metric")
+});
+
+#[derive(Debug, Clone)]
+pub(crate) struct Operation(pub(crate) Arc<str>);
+
+pub(crate) fn operation<H, T, S>(handler: H, name: &str) -> impl Handler<T, S>
+where
+    H: Handler<T, S>,
+    T: 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    let operation = Operation(to_operation_name(name).into());
+
+    handler.layer(MapResponseLayer::new(move |mut response: Response| {
+        response.extensions_mut().insert(operation.clone());
+        response
+    }))
+}
+
+fn to_operation_name(name: &str) -> String {
+    if !name
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == ':')
+    {
+        return UNKNOWN_OPERATION.to_owned();
+    }
+    let name = name.rsplit("::").next().unwrap_or(name);
+    let name = name.strip_suffix("_handler").unwrap_or(name);
+
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn record_metrics(request: Request, next: Next) -> Response {
+    let start_time = Instant::now();
+    let received = Arc::new(AtomicU64::new(0));
+    let request = request.map(|body| {
+        let received = Arc::clone(&received);
+        Body::new(body.map_frame(move |frame| {
+            if let Some(data) = frame.data_ref() {
+                received.fetch_add(data.len() as u64, Ordering::Relaxed);
+            }
+            frame
+        }))
+    });
+
+    let response = next.run(request).await;
+
+    let operation = response.extensions().get::<Operation>().map_or_else(
+        || UNKNOWN_OPERATION.into(),
+        |Operation(name)| Arc::clone(name),
+    );
+    let operation = &*operation;
+    REQUESTS
+        .with_label_values(&[operation, response.status().as_str()])
+        .inc();
+    if let Some(err) = response.extensions().get::<AppErrorDiscriminants>() {
+        ERRORS
+            .with_label_values(&[operation, &err.to_string()])
+            .inc();
+    }
+    DURATION
+        .with_label_values(&[operation])
+        .observe(start_time.elapsed().as_secs_f64());
+    RECEIVED_BYTES
+        .with_label_values(&[operation])
+        .inc_by(received.load(Ordering::Relaxed));
+
+    let sent = SENT_BYTES.with_label_values(&[operation]);
+    if let Some(size) = response.body().size_hint().exact() {
+        sent.inc_by(size);
+        response
+    } else {
+        response.map(|body| {
+            Body::new(body.map_frame(move |frame| {
+                if let Some(data) = frame.data_ref() {
+                    sent.inc_by(data.len() as u64);
+                }
+                frame
+            }))
+        })
+    }
+}
+
+pub(crate) async fn encode(db_conn: &DbConn, chunk_cache: &ChunkCache) -> Result<String, DbErr> {
+    let pool = db_conn.get_postgres_connection_pool();
+    DB_CONNECTIONS.set(pool.size().into());
+    DB_IDLE_CONNECTIONS.set(pool.num_idle() as i64);
+    set_chunk_cache_stats(&chunk_cache.stats());
+
+    let buckets = BucketQuery::find_all_also_owner(db_conn).await?;
+    BUCKET_SIZE.reset();
+    BUCKET_OBJECTS.reset();
+    for (bucket, owner) in buckets {
+        let owner = owner.map(|owner| owner.name).unwrap_or_default();
+        let labels = [owner.as_str(), bucket.name.as_str()];
+        BUCKET_SIZE
+            .with_label_values(&labels)
+            .set(bucket.usage_size);
+        BUCKET_OBJECTS
+            .with_label_values(&labels)
+            .set(bucket.usage_objects);
+    }
+
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("failed to encode metrics");
+
+    Ok(String::from_utf8(buffer).expect("invalid metrics"))
+}
+
+fn set_chunk_cache_stats(stats: &ChunkCacheStats) {
+    for (counter, value) in [
+        (&CHUNK_CACHE_HITS, stats.hits),
+        (&CHUNK_CACHE_MISSES, stats.misses),
+        (&CHUNK_CACHE_EVICTIONS, stats.evictions),
+    ] {
+        counter.inc_by(value.saturating_sub(counter.get()));
+    }
+    CHUNK_CACHE_ENTRIES.set(stats.entries.try_into().unwrap_or(i64::MAX));
+    CHUNK_CACHE_SIZE.set(stats.size.as_u64().try_into().unwrap_or(i64::MAX));
+    CHUNK_CACHE_CAPACITY.set(stats.capacity.as_u64().try_into().unwrap_or(i64::MAX));
+}
+
+#[cfg(test)]
+mod tests {
+    use bytesize::ByteSize;
+
+    use super::*;
+
+    #[test]
+    fn test_to_operation_name() {
+        assert_eq!(to_operation_name("get_object"), "GetObject");
+        assert_eq!(to_operation_name("list_objects_v2"), "ListObjectsV2");
+        assert_eq!(
+            to_operation_name("put_object_tagging_handler"),
+            "PutObjectTagging"
+        );
+        assert_eq!(
+            to_operation_name("minil::complete_multipart_upload_handler"),
+            "CompleteMultipartUpload"
+        );
+        assert_eq!(to_operation_name("{{closure}}"), UNKNOWN_OPERATION);
+        assert_eq!(to_operation_name("get object"), UNKNOWN_OPERATION);
+    }
+
+    #[test]
+    fn test_set_chunk_cache_stats() {
+        let mut stats = ChunkCacheStats {
+            hits: 3,
+            misses: 2,
+            evictions: 1,
+            entries: 4,
+            size: ByteSize::kib(4),
+            capacity: ByteSize::mib(1),
+        };
+        set_chunk_cache_stats(&stats);
+        stats.hits = 5;
+        set_chunk_cache_stats(&stats);
+
+        assert_eq!(CHUNK_CACHE_HITS.get(), 5);
+        assert_eq!(CHUNK_CACHE_MISSES.get(), 2);
+        assert_eq!(CHUNK_CACHE_EVICTIONS.get(), 1);
+        assert_eq!(CHUNK_CACHE_ENTRIES.get(), 4);
+        assert_eq!(CHUNK_CACHE_SIZE.get(), 4096);
+        assert_eq!(CHUNK_CACHE_CAPACITY.get(), 1024 * 1024);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use axum::extract::Request;
+use axum::extract::State;
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::Response;
+use futures::TryStreamExt;
+use minil_config::AppConfig;
+use minil_entity::bucket;
+use minil_entity::owner;
+use minil_entity::version;
+use minil_service::prelude::*;
+use sea_orm::DbConn;
+use sea_orm::TransactionTrait;
+use sea_orm::prelude::DateTimeUtc;
+use tokio::fs::File;
+use tower_http::BoxError;
+use tracing::debug;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::error::AppResult;
+
+const MOUNT_OWNER: &str = "minil";
+
+fn last_modified(metadata: &Metadata) -> io::Result<DateTimeUtc> {
+    let secs = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_err(io::Error::other)?
+        .as_secs();
+
+    Ok(DateTimeUtc::from(UNIX_EPOCH + Duration::from_secs(secs)))
+}
+
+async fn find_or_insert_bucket(db_conn: &DbConn, bucket: &str) -> Result<bucket::Model, BoxError> {
+    let owner = OwnerQuery::find(db_conn, MOUNT_OWNER)
+        .await?
+        .ok_or("missing owner")?;
+
+    Ok(match BucketQuery::find(db_conn, owner.id, bucket).await? {
+        Some(bucket) => bucket,
+        None => BucketMutation::insert(db_conn, owner.id, bucket.to_owned())
+            .await?
+            .ok_or("failed to create bucket")?,
+    })
+}
+
+pub(crate) async fn seed(
+    db_conn: &DbConn,
+    config: &AppConfig,
+    path: &Path,
+    bucket: &str,
+) -> Result<(), BoxError> {
+    let bucket = find_or_insert_bucket(db_conn, bucket).await?;
+
+    let files = MountQuery::find_many_files(path).await?;
+    for (key, path, _) in &files {
+        let db_txn = db_conn.begin().await?;
+        ObjectMutation::upsert_also_version(
+            &db_txn,
+            db_conn,
+            bucket.id,
+            key.clone(),
+            bucket.versioning.unwrap_or_default(),
+            None,
+            None,
+            config.database.chunk_size(),
+            config.database.inline_threshold,
+            File::open(path).await?,
+        )
+        .await?;
+        db_txn.commit().await?;
+
+        debug!(key, "seeded object");
+    }
+
+    info!(bucket = bucket.name, objects = files.len(), "seeded bucket");
+
+    Ok(())
+}
+
+pub(crate) async fn scan(
+    db_conn: &DbConn,
+    bucket: &str,
+    path: &Path,
+) -> Result<(usize, usize), BoxError> {
+    let bucket = find_or_insert_bucket(db_conn, bucket).await?;
+
+    let mut versions: HashMap<String, version::Model> =
+        ObjectQuery::find_many_both_latest_version(db_conn, bucket.id, None, None, None)
+            .await?
+            .map_ok(|(object, version)| (object.key, version))
+            .try_collect()
+            .await?;
+
+    let mut updated = 0;
+    for (key, path, metadata) in MountQuery::find_many_files(path).await? {
+        let modified = last_modified(&metadata)?;
+        if let Some(version) = versions.remove(&key)
+            && version.path.as_deref() == path.to_str()
+            && version.size == Some(metadata.len() as i64)
+            && version.last_modified() == modified
+        {
+            continue;
+        }
+
+        let db_txn = db_conn.begin().await?;
+        ObjectMutation::upsert_mounted_also_version(&db_txn, bucket.id, key, &path, modified)
+            .await?;
+        db_txn.commit().await?;
+
+        updated += 1;
+    }
+
+    let deleted = versions.len();
+    for key in versions.into_keys() {
+        ObjectMutation::delete(db_conn, bucket.id, &key).await?;
+    }
+
+    Ok((updated, deleted))
+}
+
+pub(crate) async fn reject_writes(
+    State(config): State<Arc<AppConfig>>,
+    request: Request,
+    next: Next,
+) -> AppResult<Response> {
+    let owner = request.extensions().get::<owner::Model>();
+    if is_mounted_write(
+        &config,
+        owner.map(|owner| owner.name.as_str()),
+        request.method(),
+        request.uri().path(),
+    ) {
+        Err(AppError::AccessDenied)?;
+    }
+
+    Ok(next.run(request).await)
+}
+
+fn is_mounted_write(config: &AppConfig, owner: Option<&str>, method: &Method, path: &str) -> bool {
+    let (Some(bucket), Some(_)) = (&config.mount.bucket, &config.mount.path) else {
+        return false;
+    };
+
+    !matches!(*method, Method::GET | Method::HEAD)
+        && owner == Some(MOUNT_OWNER)
+        && path.split('/').nth(1) == Some(bucket.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mounted_write() {
+        let mut config = AppConfig::default();
+        config.mount.bucket = Some("mnt".to_owned());
+        let is_write = |config: &AppConfig, owner, method, path| {
+            is_mounted_write(config, owner, &method, path)
+        };
+
+        assert!(!is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::PUT,
+            "/mnt/key"
+        ));
+
+        config.mount.path = Some("/srv/mnt".into());
+        assert!(is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::PUT,
+            "/mnt/key"
+        ));
+        assert!(is_write(&config, Some(MOUNT_OWNER), Method::DELETE, "/mnt"));
+        assert!(!is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::GET,
+            "/mnt/key"
+        ));
+        assert!(!is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::HEAD,
+            "/mnt/key"
+        ));
+        assert!(!is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::PUT,
+            "/other/key"
+        ));
+        assert!(!is_write(
+            &config,
+            Some(MOUNT_OWNER),
+            Method::PUT,
+            "/mnt2/key"
+        ));
+        assert!(!is_write(&config, Some("alice"), Method::PUT, "/mnt/key"));
+        assert!(!is_write(&config, None, Method::PUT, "/mnt/key"));
+    }
+}
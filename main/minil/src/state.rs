@@ -1,8 +1,22 @@
+use std::sync::Arc;
+
 use axum::extract::FromRef;
 use derive_more::Constructor;
+use minil_config::AppConfig;
+use minil_service::ChunkCache;
+use minil_service::ChunkStorage;
 use sea_orm::DbConn;
+use tokio::sync::Mutex;
 
 #[derive(Debug, Clone, Constructor, FromRef)]
 pub(crate) struct AppState {
+    pub(crate) config: Arc<AppConfig>,
+
     pub(crate) db_conn: DbConn,
+
+    pub(crate) chunk_cache: Arc<ChunkCache>,
+
+    pub(crate) chunk_storage: Arc<ChunkStorage>,
+
+    pub(crate) scrub_lock: Arc<Mutex<()>>,
 }
@@ -0,0 +1,221 @@
+use axum::extract::FromRequestParts;
+use axum::extract::RawPathParams;
+use axum::extract::Request;
+use axum::http::HeaderMap;
+use axum::http::HeaderName;
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_otlp::ExporterBuildError;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::trace::Tracer;
+use tracing::Span;
+use tracing::field::Empty;
+use tracing::info_span;
+use tracing::warn;
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+use crate::REQUEST_ID_HEADER;
+use crate::VERSION_ID_HEADER;
+use crate::metrics::Operation;
+
+#[derive(Debug)]
+pub(crate) struct TracerGuard(SdkTracerProvider);
+
+impl TracerGuard {
+    pub(crate) fn tracer(&self) -> Tracer {
+        self.0.tracer(env!("CARGO_PKG_NAME"))
+    }
+}
+
+impl Drop for TracerGuard {
+    fn drop(&mut self) {
+        if let Err(err) = self.0.shutdown() {
+            warn!(%err, "OtelSdkError");
+        }
+    }
+}
+
+pub(crate) fn init_tracer(endpoint: &str) -> Result<TracerGuard, ExporterBuildError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_resource(
+            Resource::builder()
+                .with_service_name(env!("CARGO_PKG_NAME"))
+                .build(),
+        )
+        .with_batch_exporter(exporter)
+        .build();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(TracerGuard(provider))
+}
+
+pub(crate) fn make_span(request: &Request) -> Span {
+    let span = info_span!(
+        "request",
+        otel.name = Empty,
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.request.method = %request.method(),
+        url.path = request.uri().path(),
+        http.response.status_code = Empty,
+        http.request.body.size = Empty,
+        http.response.body.size = Empty,
+        aws.request_id = Empty,
+        aws.s3.operation = Empty,
+        aws.s3.bucket = Empty,
+        aws.s3.key = Empty,
+        aws.s3.version_id = Empty,
+    );
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    let _ = span.set_parent(parent);
+
+    span
+}
+
+pub(crate) async fn record_span(request: Request, next: Next) -> Response {
+    let span = Span::current();
+
+    let (mut parts, body) = request.into_parts();
+    if let Ok(params) = RawPathParams::from_request_parts(&mut parts, &()).await {
+        for (name, value) in &params {
+            match name {
+                "Bucket" => {
+                    span.record("aws.s3.bucket", value);
+                }
+                "Key" => {
+                    span.record("aws.s3.key", value);
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(request_id) = find_header(&parts.headers, &REQUEST_ID_HEADER) {
+        span.record("aws.request_id", request_id);
+    }
+    if let Some(size) = find_header(&parts.headers, &header::CONTENT_LENGTH) {
+        span.record("http.request.body.size", size);
+    }
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    if let Some(Operation(operation)) = response.extensions().get::<Operation>() {
+        span.record("otel.name", &**operation);
+        span.record("aws.s3.operation", &**operation);
+    }
+    if let Some(version_id) = find_header(response.headers(), &VERSION_ID_HEADER) {
+        span.record("aws.s3.version_id", version_id);
+    }
+    if let Some(size) = find_header(response.headers(), &header::CONTENT_LENGTH) {
+        span.record("http.response.body.size", size);
+    }
+    span.record("http.response.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+
+    response
+}
+
+fn find_header<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::Router;
+    use axum::body::Body;
+    use axum::body::Bytes;
+    use axum::http::StatusCode;
+    use axum::middleware;
+    use axum::routing::put;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio::time;
+    use tower::ServiceExt as _;
+    use tower_http::trace::TraceLayer;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+    use crate::metrics;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    async fn put_object() -> ([(HeaderName, &'static str); 1], StatusCode) {
+        ([(VERSION_ID_HEADER, "v1")], StatusCode::OK)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_request_span() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let collector = Router::new().route(
+            "/v1/traces",
+            axum::routing::post(move |body: Bytes| async move {
+                sender.send(body).unwrap();
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, collector).await });
+
+        let tracer = init_tracer(&endpoint).unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer.tracer()));
+        let router = Router::new()
+            .route(
+                "/{Bucket}/{*Key}",
+                put(metrics::operation(put_object, "put_object")),
+            )
+            .layer(middleware::from_fn(record_span))
+            .layer(TraceLayer::new_for_http().make_span_with(make_span));
+
+        let request = Request::put("/bkt/dir/key")
+            .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+            .header(REQUEST_ID_HEADER, "request-id")
+            .header(header::CONTENT_LENGTH, "5")
+            .body(Body::from("hello"))
+            .unwrap();
+        let status = {
+            let _guard = tracing::subscriber::set_default(subscriber);
+            router.oneshot(request).await.unwrap().status()
+        };
+        assert_eq!(status, StatusCode::OK);
+        tokio::task::spawn_blocking(move || drop(tracer))
+            .await
+            .unwrap();
+
+        let body = time::timeout(Duration::from_secs(10), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let contains = |needle: &[u8]| body.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(&hex::decode(TRACE_ID).unwrap()));
+        for needle in [
+            "PutObject",
+            "aws.s3.bucket",
+            "bkt",
+            "aws.s3.key",
+            "dir/key",
+            "aws.s3.version_id",
+            "aws.request_id",
+            "request-id",
+        ] {
+            assert!(contains(needle.as_bytes()), "missing {needle}");
+        }
+    }
+}
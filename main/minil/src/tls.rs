@@ -0,0 +1,218 @@
+use std::fs;
+use std::future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use axum::extract::connect_info::Connected;
+use axum::serve::IncomingStream;
+use axum::serve::Listener;
+use minil_config::AppConfig;
+use parking_lot::RwLock;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::RootCertStore;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::pki_types::CertificateDer;
+use tokio_rustls::rustls::pki_types::PrivateKeyDer;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::server::TlsStream;
+use tower_http::BoxError;
+use tracing::debug;
+use tracing::warn;
+use x509_parser::prelude::FromDer;
+use x509_parser::prelude::X509Certificate;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub(crate) struct ClientOwner {
+    pub(crate) addr: SocketAddr,
+
+    pub(crate) name: Option<String>,
+}
+
+impl Connected<IncomingStream<'_, TcpListener>> for ClientOwner {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self {
+            addr: *stream.remote_addr(),
+            name: None,
+        }
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for ClientOwner {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        let name = stream
+            .io()
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(find_common_name);
+
+        Self {
+            addr: *stream.remote_addr(),
+            name,
+        }
+    }
+}
+
+pub(crate) struct TlsListener {
+    receiver: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub(crate) fn new(
+        listener: TcpListener,
+        acceptor: Arc<RwLock<TlsAcceptor>>,
+    ) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, receiver) = mpsc::channel(64);
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        warn!(%err, "failed to accept connection");
+                        time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.read().clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, addr)).await;
+                        }
+                        Ok(Err(err)) => debug!(%err, %addr, "tls handshake failed"),
+                        Err(_) => debug!(%addr, "tls handshake timed out"),
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            receiver,
+            local_addr,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.receiver.recv().await {
+            Some(conn) => conn,
+            None => future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+fn find_common_name(cert: &CertificateDer) -> Option<String> {
+    let (_, cert) = X509Certificate::from_der(cert).ok()?;
+    let name = cert.subject().iter_common_name().next()?.as_str().ok()?;
+
+    Some(name.to_owned())
+}
+
+pub(crate) fn find_modified(config: &AppConfig) -> Vec<Option<SystemTime>> {
+    let tls = &config.server.tls;
+
+    [&tls.cert, &tls.key, &tls.ca]
+        .into_iter()
+        .flatten()
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+pub(crate) fn load_acceptor(config: &AppConfig) -> Result<Option<TlsAcceptor>, BoxError> {
+    let tls = &config.server.tls;
+    let (Some(cert), Some(key)) = (&tls.cert, &tls.key) else {
+        return Ok(None);
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert)?.collect::<Result<Vec<_>, _>>()?;
+    let key = PrivateKeyDer::from_pem_file(key)?;
+
+    let builder = ServerConfig::builder();
+    let builder = match &tls.ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca)? {
+                roots.add(cert?)?;
+            }
+
+            builder
+                .with_client_cert_verifier(WebPkiClientVerifier::builder(Arc::new(roots)).build()?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config = builder.with_single_cert(certs, key)?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Some(TlsAcceptor::from(Arc::new(server_config))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMED_CERT: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBljCCAT2gAwIBAgIUSW8oKfp3kUqROG5topNW8vIzam0wCgYIKoZIzj0EAwIw
+IDEOMAwGA1UECgwFbWluaWwxDjAMBgNVBAMMBWFsaWNlMCAXDTI2MTAxOTA5NDM0
+MFoYDzIxMjYwOTI1MDk0MzQwWjAgMQ4wDAYDVQQKDAVtaW5pbDEOMAwGA1UEAwwF
+YWxpY2UwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAATtrQDT/OLMYUtqBXVlMHli
+7UMau0oNv/EgnaV1KaCbQfT7E75GMdDmN7N8eytAFbJepvSmGtwvYo4dRz0PaIby
+o1MwUTAdBgNVHQ4EFgQUq/IH9qpJnpmY9PjR9RXVHiO1fhIwHwYDVR0jBBgwFoAU
+q/IH9qpJnpmY9PjR9RXVHiO1fhIwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQD
+AgNHADBEAiA5WSNV7Pz0FJhUOq0ZxkRXT9R+pur7REIDcloDEp3vpAIgEroTKzVV
+ZuZ3FU2Wo7b3hrpy1tODzmTdkzwh+RDCfu4=
+-----END CERTIFICATE-----";
+    const UNNAMED_CERT: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBdzCCAR2gAwIBAgIUBv/rkJ//inWVBsSkAkl/+TdZskswCgYIKoZIzj0EAwIw
+EDEOMAwGA1UECgwFbWluaWwwIBcNMjYxMDE5MDk0MzQwWhgPMjEyNjA5MjUwOTQz
+NDBaMBAxDjAMBgNVBAoMBW1pbmlsMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE
+ytvYIXbdd0E3DcDCwFRUStL0WQZ+ytoD7RvNYFwunLksvCbrmwoj8Q9cj5eNYEdQ
+rtyvgyI9mvFg6QDBniVy7KNTMFEwHQYDVR0OBBYEFOH/slyujkhq4KWzjxO1W1yL
+tktKMB8GA1UdIwQYMBaAFOH/slyujkhq4KWzjxO1W1yLtktKMA8GA1UdEwEB/wQF
+MAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIgG9UMqp5ddpy7BaBaP5jCWHZVRCNfd/Ii
+ccSfJ3lGvXcCIQC7xh9/ygGa6Sus/PW30Drhf7hGfFy7m32ijBMikxxZkw==
+-----END CERTIFICATE-----";
+
+    fn find_pem_common_name(pem: &str) -> Option<String> {
+        find_common_name(&CertificateDer::from_pem_slice(pem.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn test_find_common_name() {
+        assert_eq!(find_pem_common_name(NAMED_CERT).as_deref(), Some("alice"));
+        assert_eq!(find_pem_common_name(UNNAMED_CERT), None);
+        assert_eq!(
+            find_common_name(&CertificateDer::from(&b"not a cert"[..])),
+            None
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use axum::extract::Request;
+use axum::http::Uri;
+use axum::http::header;
+use axum::http::uri::PathAndQuery;
+
+fn find_bucket<'a>(domain: &str, host: &'a str) -> Option<&'a str> {
+    let domain = domain.trim_matches('.');
+    let host = host.split_once(':').map_or(host, |(host, _)| host);
+    let (bucket, suffix) = host.split_at_checked(host.len().checked_sub(domain.len())?)?;
+    let bucket = bucket.strip_suffix('.')?;
+
+    (!bucket.is_empty() && suffix.eq_ignore_ascii_case(domain)).then_some(bucket)
+}
+
+pub(crate) fn rewrite_request(domain: &str, mut request: Request) -> Request {
+    let host = request.uri().host().map(str::to_owned).or_else(|| {
+        request
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .map(str::to_owned)
+    });
+    let Some(bucket) = host
+        .as_deref()
+        .and_then(|host| find_bucket(domain, host))
+        .map(str::to_ascii_lowercase)
+    else {
+        return request;
+    };
+
+    let path = match request.uri().path() {
+        "/" => format!("/{bucket}"),
+        path => format!("/{bucket}{path}"),
+    };
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path,
+    };
+
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = path_and_query.parse::<PathAndQuery>().ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *request.uri_mut() = uri;
+    }
+
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+
+    use super::*;
+
+    #[test]
+    fn test_find_bucket() {
+        assert_eq!(find_bucket("s3.local", "bkt.s3.local"), Some("bkt"));
+        assert_eq!(find_bucket(".s3.local.", "bkt.s3.local"), Some("bkt"));
+        assert_eq!(
+            find_bucket("s3.local", "my.dotted.bkt.s3.local"),
+            Some("my.dotted.bkt")
+        );
+        assert_eq!(find_bucket("s3.local", "Bkt.S3.Local:3000"), Some("Bkt"));
+        assert_eq!(find_bucket("s3.local", "s3.local"), None);
+        assert_eq!(find_bucket("s3.local", "s3.local:3000"), None);
+        assert_eq!(find_bucket("s3.local", ".s3.local"), None);
+        assert_eq!(find_bucket("s3.local", "bkts3.local"), None);
+        assert_eq!(find_bucket("s3.local", "bkt.s3.other"), None);
+        assert_eq!(find_bucket("s3.local", "local"), None);
+    }
+
+    #[test]
+    fn test_rewrite_request() {
+        let rewrite = |host: &str, uri: &str| {
+            let request = Request::get(uri)
+                .header(header::HOST, host)
+                .body(Body::empty())
+                .unwrap();
+            rewrite_request("s3.local", request).uri().to_string()
+        };
+
+        assert_eq!(rewrite("Bkt.s3.local:3000", "/"), "/bkt");
+        assert_eq!(
+            rewrite("bkt.s3.local", "/dir/key?tagging"),
+            "/bkt/dir/key?tagging"
+        );
+        assert_eq!(rewrite("s3.local", "/bkt/key"), "/bkt/key");
+    }
+}
@@ -2,6 +2,7 @@ use futures::Stream;
 use futures::TryStreamExt;
 use minil_entity::bucket;
 use minil_entity::object;
+use minil_entity::owner;
 use minil_entity::prelude::*;
 use minil_entity::tag_set;
 use minil_entity::upload;
@@ -28,6 +29,16 @@ impl BucketQuery {
             .await
     }
 
+    pub async fn find_for_update(
+        db: &impl ConnectionTrait,
+        id: Uuid,
+    ) -> DbRes<Option<bucket::Model>> {
+        Bucket::find_by_id(id)
+            .lock(LockType::NoKeyUpdate)
+            .one(db)
+            .await
+    }
+
     pub async fn find_also_upload(
         db: &impl ConnectionTrait,
         owner_id: Uuid,
@@ -93,6 +104,26 @@ impl BucketQuery {
             .stream(db)
             .await
     }
+
+    pub async fn find_all_also_owner(
+        db: &impl ConnectionTrait,
+    ) -> DbRes<Vec<(bucket::Model, Option<owner::Model>)>> {
+        Bucket::find()
+            .find_also_related(Owner)
+            .order_by_asc(bucket::Column::Name)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_many_logging_also_owner(
+        db: &impl ConnectionTrait,
+    ) -> DbRes<Vec<(bucket::Model, Option<owner::Model>)>> {
+        Bucket::find()
+            .find_also_related(Owner)
+            .filter(bucket::Column::LoggingTarget.is_not_null())
+            .all(db)
+            .await
+    }
 }
 
 pub struct BucketMutation;
@@ -149,6 +180,54 @@ impl BucketMutation {
             .await
     }
 
+    pub async fn update_quota(
+        db: &(impl ConnectionTrait + StreamTrait),
+        owner_id: Uuid,
+        name: &str,
+        size: Option<i64>,
+        objects: Option<i64>,
+    ) -> DbRes<Option<bucket::Model>> {
+        let bucket = bucket::ActiveModel {
+            quota_size: Set(size),
+            quota_objects: Set(objects),
+            ..Default::default()
+        };
+
+        Bucket::update_many()
+            .filter(bucket::Column::OwnerId.eq(owner_id))
+            .filter(bucket::Column::Name.eq(name))
+            .set(bucket)
+            .col_expr(bucket::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
+    pub async fn update_logging(
+        db: &(impl ConnectionTrait + StreamTrait),
+        owner_id: Uuid,
+        name: &str,
+        target: Option<(String, String)>,
+    ) -> DbRes<Option<bucket::Model>> {
+        let (target, prefix) = target.unzip();
+        let bucket = bucket::ActiveModel {
+            logging_target: Set(target),
+            logging_prefix: Set(prefix),
+            ..Default::default()
+        };
+
+        Bucket::update_many()
+            .filter(bucket::Column::OwnerId.eq(owner_id))
+            .filter(bucket::Column::Name.eq(name))
+            .set(bucket)
+            .col_expr(bucket::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
     pub async fn delete(
         db: &(impl ConnectionTrait + StreamTrait),
         owner_id: Uuid,
@@ -163,3 +242,44 @@ impl BucketMutation {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_db;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_find_many_logging_also_owner() {
+        let db_conn = test_db::connect().await;
+        let (owner, bucket) = test_db::create_bucket(&db_conn).await;
+        let (_, target) = test_db::create_bucket(&db_conn).await;
+        let find_logging = async || {
+            BucketQuery::find_many_logging_also_owner(&db_conn)
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|(logging, _)| logging.id == bucket.id)
+        };
+        assert!(find_logging().await.is_none());
+
+        BucketMutation::update_logging(
+            &db_conn,
+            owner.id,
+            &bucket.name,
+            Some((target.name.clone(), "logs/".to_owned())),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let (logging, logging_owner) = find_logging().await.unwrap();
+        assert_eq!(logging.logging_target, Some(target.name));
+        assert_eq!(logging_owner.map(|owner| owner.id), Some(owner.id));
+
+        BucketMutation::update_logging(&db_conn, owner.id, &bucket.name, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(find_logging().await.is_none());
+    }
+}
@@ -1,27 +1,52 @@
+use std::collections::HashSet;
 use std::ops::RangeInclusive;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use async_stream::try_stream;
 use bytes::Bytes;
+use bytesize::ByteSize;
 use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
 use minil_entity::chunk;
 use minil_entity::object;
 use minil_entity::prelude::*;
+use minil_entity::sea_orm_active_enums::ChunkTier;
+use minil_entity::version_part;
 use sea_orm::prelude::Expr;
 use sea_orm::*;
+use tokio::io::AsyncRead;
+use tokio::time;
+use tokio_util::codec::FramedRead;
 use uuid::Uuid;
 
+use crate::ChunkCache;
+use crate::ChunkStorage;
+use crate::InsErr;
+use crate::InsRes;
+use crate::StagingMutation;
 use crate::VersionPartQuery;
 use crate::error::DbRes;
+use crate::utils::Checksum;
+use crate::utils::ChecksumDigest;
+use crate::utils::ChunkDecoder;
+
+const STAGING_HEARTBEAT: Duration = Duration::from_secs(10);
 
 pub struct ChunkQuery;
 
 impl ChunkQuery {
-    async fn find_many_by_version_part_id(
-        db: &(impl ConnectionTrait + StreamTrait),
+    pub(super) async fn find_many_ranged_by_version_part_id(
+        db: &impl ConnectionTrait,
         version_part_id: Uuid,
         range: Option<&RangeInclusive<u64>>,
-    ) -> DbRes<impl Stream<Item = DbRes<chunk::Model>>> {
+    ) -> DbRes<Vec<(Uuid, i64, i64)>> {
         Chunk::find()
+            .select_only()
+            .columns([chunk::Column::Id, chunk::Column::Start, chunk::Column::End])
             .filter(chunk::Column::VersionPartId.eq(version_part_id))
             .apply_if(range, |query, range| {
                 query
@@ -29,24 +54,140 @@ impl ChunkQuery {
                     .filter(chunk::Column::End.gte(*range.start()))
             })
             .order_by_asc(chunk::Column::Index)
-            .stream(db)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    pub(super) async fn find_many_by_upload_part_id(
+        db: &impl ConnectionTrait,
+        upload_part_id: Uuid,
+    ) -> DbRes<Vec<(Uuid, i64, i64)>> {
+        Chunk::find()
+            .select_only()
+            .columns([chunk::Column::Id, chunk::Column::Start, chunk::Column::End])
+            .filter(chunk::Column::UploadPartId.eq(upload_part_id))
+            .order_by_asc(chunk::Column::Index)
+            .into_tuple()
+            .all(db)
             .await
     }
 
+    pub(super) async fn count_staged(db: &impl ConnectionTrait) -> DbRes<u64> {
+        Chunk::find()
+            .filter(chunk::Column::StagingId.is_not_null())
+            .count(db)
+            .await
+    }
+
+    async fn find_many_by_version_id_and_tier_ne(
+        db: &impl ConnectionTrait,
+        version_id: Uuid,
+        tier: ChunkTier,
+    ) -> DbRes<Vec<(Uuid, ChunkTier)>> {
+        Chunk::find()
+            .select_only()
+            .columns([chunk::Column::Id, chunk::Column::Tier])
+            .inner_join(VersionPart)
+            .filter(version_part::Column::VersionId.eq(version_id))
+            .filter(chunk::Column::Tier.ne(tier))
+            .order_by_asc(chunk::Column::Index)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    pub async fn find_many_degraded_version_ids(db: &impl ConnectionTrait) -> DbRes<Vec<Uuid>> {
+        Chunk::find()
+            .select_only()
+            .column(version_part::Column::VersionId)
+            .distinct()
+            .inner_join(VersionPart)
+            .filter(chunk::Column::DegradedAt.is_not_null())
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    async fn find_many_degraded(
+        db: &impl ConnectionTrait,
+        limit: u64,
+    ) -> DbRes<Vec<(Uuid, ChunkTier)>> {
+        Chunk::find()
+            .select_only()
+            .columns([chunk::Column::Id, chunk::Column::Tier])
+            .filter(chunk::Column::DegradedAt.is_not_null())
+            .order_by_asc(chunk::Column::DegradedAt)
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    async fn find_many_ids_by_tier(
+        db: &impl ConnectionTrait,
+        ids: impl IntoIterator<Item = Uuid>,
+        tier: ChunkTier,
+    ) -> DbRes<Vec<Uuid>> {
+        Chunk::find()
+            .select_only()
+            .column(chunk::Column::Id)
+            .filter(chunk::Column::Id.is_in(ids))
+            .filter(chunk::Column::Tier.eq(tier))
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    pub(super) async fn find_data(
+        db: &impl ConnectionTrait,
+        storage: &ChunkStorage,
+        id: Uuid,
+    ) -> InsRes<Bytes> {
+        let (tier, data) = Chunk::find_by_id(id)
+            .select_only()
+            .columns([chunk::Column::Tier, chunk::Column::Data])
+            .into_tuple::<(ChunkTier, Option<Vec<u8>>)>()
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(id.to_string()))?;
+
+        Ok(if let Some(data) = data {
+            Bytes::from(data)
+        } else {
+            let (data, degraded) = storage.read(tier, id).await?;
+            if degraded {
+                storage.extend_degraded([id]);
+            }
+
+            data
+        })
+    }
+
     //noinspection RsBorrowChecker
     pub fn find_many_ranged_part_data_by_version_part_id(
         db: impl ConnectionTrait + StreamTrait,
+        cache: Arc<ChunkCache>,
+        storage: Arc<ChunkStorage>,
+        version_id: Uuid,
         version_part_id: Uuid,
         range: Option<RangeInclusive<u64>>,
-    ) -> impl Stream<Item = DbRes<Bytes>> {
+    ) -> impl Stream<Item = InsRes<Bytes>> {
         try_stream! {
-            let chunks = ChunkQuery::find_many_by_version_part_id(&db, version_part_id, range.as_ref()).await?;
-            for await chunk in chunks {
-                let chunk = chunk?;
+            let chunks = ChunkQuery::find_many_ranged_by_version_part_id(&db, version_part_id, range.as_ref()).await?;
+            for (id, start, end) in chunks {
+                let data = if let Some(data) = cache.get(id) {
+                    data
+                } else {
+                    let data = ChunkQuery::find_data(&db, &storage, id).await?;
+                    cache.insert(version_id, id, data.clone());
+
+                    data
+                };
 
                 let range = range.as_ref().map(|range| {
-                    let start = chunk.start as u64;
-                    let end = chunk.end as u64;
+                    let start = start as u64;
+                    let end = end as u64;
                     let offset = start;
 
                     let start = (start.max(*range.start()) - offset) as usize;
@@ -54,7 +195,7 @@ impl ChunkQuery {
                     start..=end
                 });
 
-                yield Bytes::copy_from_slice(range.map_or(&chunk.data, |range| &chunk.data[range]))
+                yield range.map_or(data.clone(), |range| data.slice(range))
             }
         }
     }
@@ -62,9 +203,11 @@ impl ChunkQuery {
     //noinspection RsBorrowChecker
     pub fn find_many_ranged_version_data_by_version_id(
         db: impl Clone + ConnectionTrait + StreamTrait,
+        cache: Arc<ChunkCache>,
+        storage: Arc<ChunkStorage>,
         version_id: Uuid,
         range: Option<RangeInclusive<u64>>,
-    ) -> impl Stream<Item = DbRes<Bytes>> {
+    ) -> impl Stream<Item = InsRes<Bytes>> {
         try_stream! {
             let parts = VersionPartQuery::find_many_ranged(&db, version_id, range.as_ref()).await?;
             for await part in parts {
@@ -80,7 +223,7 @@ impl ChunkQuery {
                     start..=end
                 });
 
-                let chunks = ChunkQuery::find_many_ranged_part_data_by_version_part_id(db.clone(), part.id, range);
+                let chunks = ChunkQuery::find_many_ranged_part_data_by_version_part_id(db.clone(), Arc::clone(&cache), Arc::clone(&storage), version_id, part.id, range);
                 for await chunk in chunks {
                     yield chunk?
                 }
@@ -89,13 +232,17 @@ impl ChunkQuery {
     }
 }
 
+pub struct StagedChunks {
+    pub(super) id: Uuid,
+    pub(super) checksum: Checksum,
+}
+
 pub struct ChunkMutation;
 
 impl ChunkMutation {
-    pub(super) async fn insert(
+    async fn insert_staged(
         db: &impl ConnectionTrait,
-        upload_part_id: Option<Uuid>,
-        version_part_id: Option<Uuid>,
+        staging_id: Uuid,
         index: u64,
         start: u64,
         end: u64,
@@ -103,18 +250,121 @@ impl ChunkMutation {
     ) -> DbRes<InsertResult<chunk::ActiveModel>> {
         let chunk = chunk::ActiveModel {
             id: Set(Uuid::new_v4()),
-            upload_part_id: Set(upload_part_id),
-            version_part_id: Set(version_part_id),
+            upload_part_id: Set(None),
+            version_part_id: Set(None),
+            staging_id: Set(Some(staging_id)),
             index: Set(index as i64),
             start: Set(start as i64),
             end: Set(end as i64),
-            data: Set(data),
+            tier: Set(ChunkTier::Database),
+            data: Set(Some(data)),
             ..Default::default()
         };
 
         Chunk::insert(chunk).exec(db).await
     }
 
+    pub async fn insert_many_staged(
+        db_conn: &impl ConnectionTrait,
+        chunk_size: ByteSize,
+        read: impl AsyncRead,
+    ) -> InsRes<StagedChunks> {
+        let id = StagingMutation::insert(db_conn).await?;
+
+        let decode = ChunkDecoder::with_capacity(chunk_size.as_u64() as usize);
+        let read = FramedRead::new(read, decode)
+            .enumerate()
+            .map(|(index, chunk)| chunk.map(|chunk| (index as u64, chunk)));
+        let mut stream = pin!(read);
+
+        let mut digest = ChecksumDigest::new();
+
+        loop {
+            let next = match time::timeout(STAGING_HEARTBEAT, stream.try_next()).await {
+                Ok(next) => next.map_err(InsErr::from),
+                Err(_) => match StagingMutation::touch(db_conn, id).await {
+                    Ok(()) => continue,
+                    Err(err) => Err(err.into()),
+                },
+            };
+            let (index, chunk) = match next {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(err) => {
+                    StagingMutation::delete(db_conn, id).await?;
+                    Err(err)?
+                }
+            };
+            let start = digest.size();
+
+            digest.update(&chunk);
+
+            let end = digest.size().saturating_sub(1);
+            let res =
+                ChunkMutation::insert_staged(db_conn, id, index, start, end, chunk.to_vec()).await;
+            if let Err(err) = match res {
+                Ok(_) => StagingMutation::touch(db_conn, id).await,
+                Err(err) => Err(err),
+            } {
+                StagingMutation::delete(db_conn, id).await?;
+                Err(err)?;
+            }
+        }
+
+        Ok(StagedChunks {
+            id,
+            checksum: digest.finalize(),
+        })
+    }
+
+    pub(super) async fn update_many_upload_part_id_by_staging_id(
+        db: &impl ConnectionTrait,
+        staging_id: Uuid,
+        upload_part_id: Uuid,
+    ) -> DbRes<UpdateResult> {
+        StagingMutation::lock(db, staging_id).await?;
+
+        let chunk = chunk::ActiveModel {
+            upload_part_id: Set(Some(upload_part_id)),
+            staging_id: Set(None),
+            ..Default::default()
+        };
+
+        let res = Chunk::update_many()
+            .filter(chunk::Column::StagingId.eq(staging_id))
+            .set(chunk)
+            .col_expr(chunk::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec(db)
+            .await?;
+        StagingMutation::delete(db, staging_id).await?;
+
+        Ok(res)
+    }
+
+    pub(super) async fn update_many_version_part_id_by_staging_id(
+        db: &impl ConnectionTrait,
+        staging_id: Uuid,
+        version_part_id: Uuid,
+    ) -> DbRes<UpdateResult> {
+        StagingMutation::lock(db, staging_id).await?;
+
+        let chunk = chunk::ActiveModel {
+            version_part_id: Set(Some(version_part_id)),
+            staging_id: Set(None),
+            ..Default::default()
+        };
+
+        let res = Chunk::update_many()
+            .filter(chunk::Column::StagingId.eq(staging_id))
+            .set(chunk)
+            .col_expr(chunk::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec(db)
+            .await?;
+        StagingMutation::delete(db, staging_id).await?;
+
+        Ok(res)
+    }
+
     pub(super) async fn update_many_version_part_id_by_upload_part_id(
         db: &impl ConnectionTrait,
         upload_part_id: Uuid,
@@ -134,6 +384,124 @@ impl ChunkMutation {
             .await
     }
 
+    pub async fn update_many_tier_by_version_id(
+        db: &impl ConnectionTrait,
+        storage: &ChunkStorage,
+        version_id: Uuid,
+        tier: ChunkTier,
+    ) -> InsRes<u64> {
+        let mut rows_affected = 0;
+
+        let chunks = ChunkQuery::find_many_by_version_id_and_tier_ne(db, version_id, tier).await?;
+        for (id, from) in chunks {
+            let data = ChunkQuery::find_data(db, storage, id).await?;
+            if tier != ChunkTier::Database {
+                storage.write(tier, id, &data).await?;
+            }
+
+            let chunk = chunk::ActiveModel {
+                tier: Set(tier),
+                data: Set((tier == ChunkTier::Database).then(|| data.to_vec())),
+                ..Default::default()
+            };
+
+            let res = Chunk::update_many()
+                .filter(chunk::Column::Id.eq(id))
+                .filter(chunk::Column::Tier.eq(from))
+                .set(chunk)
+                .col_expr(chunk::Column::UpdatedAt, Expr::current_timestamp().into())
+                .exec(db)
+                .await?;
+            rows_affected += res.rows_affected;
+
+            if res.rows_affected == 0 {
+                if tier != ChunkTier::Database {
+                    storage.delete(tier, id).await?;
+                }
+            } else if from != ChunkTier::Database {
+                storage.delete(from, id).await?;
+            }
+        }
+
+        Ok(rows_affected)
+    }
+
+    pub async fn update_many_degraded(
+        db: &impl ConnectionTrait,
+        ids: impl IntoIterator<Item = Uuid>,
+    ) -> DbRes<u64> {
+        let res = Chunk::update_many()
+            .filter(chunk::Column::Id.is_in(ids))
+            .filter(chunk::Column::DegradedAt.is_null())
+            .col_expr(chunk::Column::DegradedAt, Expr::current_timestamp().into())
+            .exec(db)
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+
+    pub async fn repair_many_degraded(
+        db: &impl ConnectionTrait,
+        storage: &ChunkStorage,
+        limit: u64,
+    ) -> InsRes<u64> {
+        let mut rows_affected = 0;
+
+        let chunks = ChunkQuery::find_many_degraded(db, limit).await?;
+        for (id, tier) in chunks {
+            if tier != ChunkTier::Database {
+                let Ok((data, _)) = storage.read(tier, id).await else {
+                    continue;
+                };
+                storage.write(tier, id, &data).await?;
+            }
+
+            let chunk = chunk::ActiveModel {
+                degraded_at: Set(None),
+                ..Default::default()
+            };
+
+            let res = Chunk::update_many()
+                .filter(chunk::Column::Id.eq(id))
+                .filter(chunk::Column::Tier.eq(tier))
+                .set(chunk)
+                .exec(db)
+                .await?;
+            rows_affected += res.rows_affected;
+        }
+
+        Ok(rows_affected)
+    }
+
+    pub async fn delete_many_orphaned(
+        db: &impl ConnectionTrait,
+        storage: &ChunkStorage,
+        tier: ChunkTier,
+        before: SystemTime,
+    ) -> InsRes<u64> {
+        let mut rows_affected = 0;
+
+        let ids = storage
+            .list(tier)
+            .await?
+            .into_iter()
+            .filter_map(|(id, modified)| (modified < before).then_some(id))
+            .collect::<Vec<_>>();
+        for ids in ids.chunks(1000) {
+            let found = ChunkQuery::find_many_ids_by_tier(db, ids.iter().copied(), tier)
+                .await?
+                .into_iter()
+                .collect::<HashSet<_>>();
+
+            for id in ids.iter().filter(|id| !found.contains(id)) {
+                storage.delete(tier, *id).await?;
+                rows_affected += 1;
+            }
+        }
+
+        Ok(rows_affected)
+    }
+
     pub(super) async fn delete_many_by_upload_part_id(
         db: &impl ConnectionTrait,
         upload_part_id: Uuid,
@@ -144,3 +512,172 @@ impl ChunkMutation {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use async_stream::stream;
+    use tempfile::TempDir;
+    use tokio_util::io::StreamReader;
+
+    use super::*;
+    use crate::ObjectMutation;
+    use crate::utils::test_db;
+
+    const TTL: Duration = Duration::from_millis(500);
+
+    fn slow_read(count: u8, delay: Duration) -> impl AsyncRead {
+        StreamReader::new(Box::pin(stream! {
+            for index in 0..count {
+                time::sleep(delay).await;
+                yield Ok::<_, io::Error>(Bytes::from(vec![index; 1024]));
+            }
+        }))
+    }
+
+    async fn sweep(db_conn: &DbConn) {
+        StagingMutation::delete_many_expired(db_conn, (SystemTime::now() - TTL).into())
+            .await
+            .unwrap();
+    }
+
+    async fn staged_size(db_conn: &DbConn, staging_id: Uuid) -> i64 {
+        Chunk::find()
+            .filter(chunk::Column::StagingId.eq(staging_id))
+            .all(db_conn)
+            .await
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.end - chunk.start + 1)
+            .sum()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_insert_many_staged_longer_than_ttl() {
+        let db_conn = test_db::connect().await;
+        let sweeper = tokio::spawn({
+            let db_conn = db_conn.clone();
+            async move {
+                loop {
+                    sweep(&db_conn).await;
+                    time::sleep(TTL / 5).await;
+                }
+            }
+        });
+
+        let staged =
+            ChunkMutation::insert_many_staged(&db_conn, ByteSize::kib(1), slow_read(8, TTL / 2))
+                .await
+                .unwrap();
+        sweep(&db_conn).await;
+        sweeper.abort();
+
+        assert_eq!(staged.checksum.size, 8 * 1024);
+        assert_eq!(staged_size(&db_conn, staged.id).await, 8 * 1024);
+        StagingMutation::lock(&db_conn, staged.id).await.unwrap();
+        StagingMutation::delete(&db_conn, staged.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_insert_many_staged_expired() {
+        let db_conn = test_db::connect().await;
+
+        let staged =
+            ChunkMutation::insert_many_staged(&db_conn, ByteSize::kib(1), slow_read(2, TTL / 5))
+                .await
+                .unwrap();
+        time::sleep(TTL * 2).await;
+        sweep(&db_conn).await;
+
+        assert_eq!(staged_size(&db_conn, staged.id).await, 0);
+        assert!(
+            ChunkMutation::update_many_version_part_id_by_staging_id(
+                &db_conn,
+                staged.id,
+                Uuid::new_v4()
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_repair_many_degraded() {
+        let dir = TempDir::new().unwrap();
+        let roots = (0..6).map(|index| dir.path().join(format!("disk{index}")));
+        let storage = ChunkStorage::new(None)
+            .with_erasure(roots.collect(), 4, 2)
+            .unwrap();
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &db_txn,
+            &db_conn,
+            bucket.id,
+            "degraded".to_owned(),
+            false,
+            None,
+            None,
+            ByteSize::kib(1),
+            ByteSize::kib(0),
+            vec![b'a'; 4096].as_slice(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+        ChunkMutation::update_many_tier_by_version_id(
+            &db_conn,
+            &storage,
+            version.id,
+            ChunkTier::Erasure,
+        )
+        .await
+        .unwrap();
+        std::fs::remove_dir_all(dir.path().join("disk0")).unwrap();
+
+        let chunks = ChunkQuery::find_many_by_version_id_and_tier_ne(
+            &db_conn,
+            version.id,
+            ChunkTier::Database,
+        )
+        .await
+        .unwrap();
+        assert_eq!(chunks.len(), 4);
+        for (id, _) in &chunks {
+            ChunkQuery::find_data(&db_conn, &storage, *id)
+                .await
+                .unwrap();
+        }
+        let degraded = storage.take_degraded();
+        assert!(!degraded.is_empty());
+        ChunkMutation::update_many_degraded(&db_conn, degraded)
+            .await
+            .unwrap();
+        assert!(
+            ChunkQuery::find_many_degraded_version_ids(&db_conn)
+                .await
+                .unwrap()
+                .contains(&version.id)
+        );
+
+        ChunkMutation::repair_many_degraded(&db_conn, &storage, 1000)
+            .await
+            .unwrap();
+        for (id, _) in &chunks {
+            let (_, degraded) = storage.read(ChunkTier::Erasure, *id).await.unwrap();
+            assert!(!degraded);
+        }
+        assert!(
+            !ChunkQuery::find_many_degraded_version_ids(&db_conn)
+                .await
+                .unwrap()
+                .contains(&version.id)
+        );
+    }
+}
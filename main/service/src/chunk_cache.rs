@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use bytes::Bytes;
+use bytesize::ByteSize;
+use lru::LruCache;
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub size: ByteSize,
+    pub capacity: ByteSize,
+}
+
+#[derive(Debug)]
+struct ChunkCacheEntry {
+    version_id: Uuid,
+    data: Bytes,
+}
+
+#[derive(Debug)]
+struct ChunkCacheInner {
+    entries: LruCache<Uuid, ChunkCacheEntry>,
+    versions: HashMap<Uuid, HashSet<Uuid>>,
+    size: u64,
+}
+
+impl ChunkCacheInner {
+    fn remove(&mut self, id: &Uuid) -> Option<ChunkCacheEntry> {
+        let entry = self.entries.pop(id)?;
+        self.forget(*id, &entry);
+
+        Some(entry)
+    }
+
+    fn forget(&mut self, id: Uuid, entry: &ChunkCacheEntry) {
+        self.size -= entry.data.len() as u64;
+
+        if let Some(ids) = self.versions.get_mut(&entry.version_id) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.versions.remove(&entry.version_id);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChunkCache {
+    capacity: ByteSize,
+    inner: Mutex<ChunkCacheInner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ChunkCache {
+    #[must_use]
+    pub fn new(capacity: ByteSize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(ChunkCacheInner {
+                entries: LruCache::unbounded(),
+                versions: HashMap::new(),
+                size: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Bytes> {
+        let data = self
+            .inner
+            .lock()
+            .entries
+            .get(&id)
+            .map(|entry| entry.data.clone());
+
+        if data.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        data
+    }
+
+    pub fn insert(&self, version_id: Uuid, id: Uuid, data: Bytes) {
+        let size = data.len() as u64;
+        if size > self.capacity.as_u64() {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        inner.remove(&id);
+
+        inner.size += size;
+        inner.versions.entry(version_id).or_default().insert(id);
+        inner.entries.put(id, ChunkCacheEntry { version_id, data });
+
+        while inner.size > self.capacity.as_u64() {
+            let Some((id, entry)) = inner.entries.pop_lru() else {
+                break;
+            };
+            inner.forget(id, &entry);
+
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn invalidate_version(&self, version_id: Uuid) {
+        let mut inner = self.inner.lock();
+
+        if let Some(ids) = inner.versions.remove(&version_id) {
+            for id in ids {
+                if let Some(entry) = inner.entries.pop(&id) {
+                    inner.size -= entry.data.len() as u64;
+                }
+            }
+        }
+    }
+
+    pub fn stats(&self) -> ChunkCacheStats {
+        let inner = self.inner.lock();
+
+        ChunkCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: inner.entries.len(),
+            size: ByteSize::b(inner.size),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(size: usize) -> Bytes {
+        Bytes::from(vec![0; size])
+    }
+
+    #[test]
+    fn test_get() {
+        let cache = ChunkCache::new(ByteSize::b(10));
+        let (version_id, id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(cache.get(id), None);
+        cache.insert(version_id, id, data(4));
+        assert_eq!(cache.get(id), Some(data(4)));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!((stats.entries, stats.size), (1, ByteSize::b(4)));
+    }
+
+    #[test]
+    fn test_insert_evicts_lru() {
+        let cache = ChunkCache::new(ByteSize::b(10));
+        let version_id = Uuid::new_v4();
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+        cache.insert(version_id, ids[0], data(4));
+        cache.insert(version_id, ids[1], data(4));
+        cache.get(ids[0]);
+        cache.insert(version_id, ids[2], data(4));
+
+        assert!(cache.get(ids[0]).is_some());
+        assert!(cache.get(ids[1]).is_none());
+        assert!(cache.get(ids[2]).is_some());
+        let stats = cache.stats();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.size, ByteSize::b(8));
+    }
+
+    #[test]
+    fn test_insert_too_large() {
+        let cache = ChunkCache::new(ByteSize::b(10));
+        let id = Uuid::new_v4();
+
+        cache.insert(Uuid::new_v4(), id, data(11));
+
+        assert!(cache.get(id).is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let cache = ChunkCache::new(ByteSize::b(10));
+        let (version_id, id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        cache.insert(version_id, id, data(4));
+        cache.insert(version_id, id, data(6));
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.size), (1, ByteSize::b(6)));
+    }
+
+    #[test]
+    fn test_invalidate_version() {
+        let cache = ChunkCache::new(ByteSize::b(10));
+        let (version_id, other_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+        cache.insert(version_id, ids[0], data(2));
+        cache.insert(version_id, ids[1], data(2));
+        cache.insert(other_id, ids[2], data(2));
+        cache.invalidate_version(version_id);
+
+        assert!(cache.get(ids[0]).is_none());
+        assert!(cache.get(ids[1]).is_none());
+        assert!(cache.get(ids[2]).is_some());
+        assert_eq!(cache.stats().size, ByteSize::b(2));
+    }
+}
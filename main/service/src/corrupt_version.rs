@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use bytesize::ByteSize;
+use futures::TryStreamExt;
+use minil_entity::bucket;
+use minil_entity::corrupt_version;
+use minil_entity::object;
+use minil_entity::prelude::*;
+use minil_entity::version;
+use minil_entity::version_part;
+use sea_orm::prelude::*;
+use sea_orm::*;
+use sea_query::*;
+
+use crate::ChunkQuery;
+use crate::ChunkStorage;
+use crate::InsErr;
+use crate::InsRes;
+use crate::VersionPartQuery;
+use crate::error::DbRes;
+use crate::utils::Checksum;
+use crate::utils::ChecksumDigest;
+
+pub type CorruptVersionObject = (String, String, Uuid, Option<Uuid>, String, DateTimeUtc);
+
+pub struct CorruptVersionQuery;
+
+impl CorruptVersionQuery {
+    pub async fn exists(db: &impl ConnectionTrait, version_id: Uuid) -> DbRes<bool> {
+        CorruptVersion::find()
+            .filter(corrupt_version::Column::VersionId.eq(version_id))
+            .count(db)
+            .await
+            .map(|count| count > 0)
+    }
+
+    pub async fn find_many_also_object(
+        db: &impl ConnectionTrait,
+    ) -> DbRes<Vec<CorruptVersionObject>> {
+        CorruptVersion::find()
+            .select_only()
+            .column(bucket::Column::Name)
+            .column(object::Column::Key)
+            .columns([
+                corrupt_version::Column::VersionId,
+                corrupt_version::Column::VersionPartId,
+                corrupt_version::Column::Reason,
+            ])
+            .expr(Func::coalesce([
+                Expr::col((CorruptVersion, corrupt_version::Column::UpdatedAt)).into(),
+                Expr::col((CorruptVersion, corrupt_version::Column::CreatedAt)).into(),
+            ]))
+            .inner_join(Version)
+            .join(JoinType::InnerJoin, version::Relation::Object.def())
+            .join(JoinType::InnerJoin, object::Relation::Bucket.def())
+            .order_by_asc(bucket::Column::Name)
+            .order_by_asc(object::Column::Key)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+}
+
+pub struct CorruptVersionMutation;
+
+impl CorruptVersionMutation {
+    async fn verify_part(
+        db: &impl ConnectionTrait,
+        storage: &ChunkStorage,
+        version_part: &version_part::Model,
+        rate: Option<ByteSize>,
+    ) -> InsRes<Option<String>> {
+        let mut digest = ChecksumDigest::new();
+
+        let chunks =
+            ChunkQuery::find_many_ranged_by_version_part_id(db, version_part.id, None).await?;
+        for (id, _, _) in chunks {
+            let data = match ChunkQuery::find_data(db, storage, id).await {
+                Ok(data) => data,
+                Err(InsErr::IoError(err)) => {
+                    return Ok(Some(format!("unreadable chunk {id}: {err}")));
+                }
+                Err(err) => return Err(err),
+            };
+            digest.update(&data);
+
+            if let Some(rate) = rate.filter(|rate| rate.as_u64() > 0) {
+                tokio::time::sleep(Duration::from_nanos(
+                    (data.len() as u64).saturating_mul(1_000_000_000) / rate.as_u64(),
+                ))
+                .await;
+            }
+        }
+
+        Ok(Checksum::from(version_part)
+            .mismatch(&digest.finalize())
+            .map(|field| format!("{field} mismatch")))
+    }
+
+    fn verify_data(version: &version::Model, data: &[u8]) -> Option<String> {
+        let mut digest = ChecksumDigest::new();
+        digest.update(data);
+
+        Checksum::from(version)
+            .mismatch(&digest.finalize())
+            .map(|field| format!("{field} mismatch"))
+    }
+
+    pub async fn scrub(
+        db: &(impl ConnectionTrait + StreamTrait + TransactionTrait),
+        storage: &ChunkStorage,
+        version: &version::Model,
+        rate: Option<ByteSize>,
+    ) -> InsRes<Option<corrupt_version::Model>> {
+        let corrupt = if let Some(data) = &version.data {
+            Self::verify_data(version, data).map(|reason| (None, reason))
+        } else {
+            let version_parts: Vec<_> = VersionPartQuery::find_many_ranged(db, version.id, None)
+                .await?
+                .try_collect()
+                .await?;
+
+            let mut corrupt = None;
+            for version_part in version_parts {
+                if let Some(reason) = Self::verify_part(db, storage, &version_part, rate).await? {
+                    corrupt = Some((Some(version_part.id), reason));
+                    break;
+                }
+            }
+
+            corrupt
+        };
+
+        // the version may have been overwritten in place while it was being read
+        let db_txn = db.begin().await?;
+        let current = Version::find_by_id(version.id)
+            .lock_shared()
+            .one(&db_txn)
+            .await?;
+        if !current.is_some_and(|current| {
+            current.last_modified() == version.last_modified()
+                && current.e_tag == version.e_tag
+                && current.md5 == version.md5
+        }) {
+            return Ok(None);
+        }
+
+        let corrupt_version = if let Some((version_part_id, reason)) = corrupt {
+            Some(Self::upsert(&db_txn, version.id, version_part_id, reason).await?)
+        } else {
+            Self::delete_by_version_id(&db_txn, version.id).await?;
+
+            None
+        };
+        db_txn.commit().await?;
+
+        Ok(corrupt_version)
+    }
+
+    pub(super) async fn upsert(
+        db: &impl ConnectionTrait,
+        version_id: Uuid,
+        version_part_id: Option<Uuid>,
+        reason: String,
+    ) -> DbRes<corrupt_version::Model> {
+        let corrupt_version = corrupt_version::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            version_id: Set(version_id),
+            version_part_id: Set(version_part_id),
+            reason: Set(reason),
+            ..Default::default()
+        };
+
+        CorruptVersion::insert(corrupt_version)
+            .on_conflict(
+                OnConflict::column(corrupt_version::Column::VersionId)
+                    .update_columns([
+                        corrupt_version::Column::VersionPartId,
+                        corrupt_version::Column::Reason,
+                    ])
+                    .value(
+                        corrupt_version::Column::UpdatedAt,
+                        Expr::current_timestamp(),
+                    )
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await
+    }
+
+    pub(super) async fn delete_by_version_id(
+        db: &impl ConnectionTrait,
+        version_id: Uuid,
+    ) -> DbRes<DeleteResult> {
+        // serializes with a concurrent scrub of the same version
+        Version::find_by_id(version_id)
+            .lock_exclusive()
+            .one(db)
+            .await?;
+
+        CorruptVersion::delete_many()
+            .filter(corrupt_version::Column::VersionId.eq(version_id))
+            .exec(db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectMutation;
+    use crate::utils::test_db;
+
+    async fn upsert(db_conn: &DbConn, bucket_id: Uuid, data: &[u8]) -> version::Model {
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &db_txn,
+            db_conn,
+            bucket_id,
+            "scrub".to_owned(),
+            false,
+            None,
+            None,
+            ByteSize::kib(1),
+            ByteSize::kib(4),
+            data,
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        version
+    }
+
+    async fn scrub_corrupted(db_conn: &DbConn, mut version: version::Model) -> bool {
+        version.data = Some(b"corrupt".to_vec());
+
+        CorruptVersionMutation::scrub(db_conn, &ChunkStorage::new(None), &version, None)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_scrub() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+
+        let version = upsert(&db_conn, bucket.id, b"data").await;
+        assert!(scrub_corrupted(&db_conn, version.clone()).await);
+        assert!(
+            CorruptVersionQuery::exists(&db_conn, version.id)
+                .await
+                .unwrap()
+        );
+
+        CorruptVersionMutation::scrub(&db_conn, &ChunkStorage::new(None), &version, None)
+            .await
+            .unwrap();
+        assert!(
+            !CorruptVersionQuery::exists(&db_conn, version.id)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_scrub_overwritten() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+
+        let stale = upsert(&db_conn, bucket.id, b"data").await;
+        let version = upsert(&db_conn, bucket.id, b"data").await;
+        assert_eq!(stale.id, version.id);
+
+        assert!(!scrub_corrupted(&db_conn, stale).await);
+        assert!(
+            !CorruptVersionQuery::exists(&db_conn, version.id)
+                .await
+                .unwrap()
+        );
+    }
+}
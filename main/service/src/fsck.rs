@@ -0,0 +1,403 @@
+use std::time::SystemTime;
+
+use derive_more::Display;
+use minil_entity::object;
+use minil_entity::prelude::*;
+use minil_entity::tag_set;
+use minil_entity::upload_part;
+use minil_entity::version;
+use minil_entity::version_part;
+use sea_orm::prelude::*;
+use sea_orm::*;
+use sea_query::*;
+
+use crate::ChunkQuery;
+use crate::CorruptVersionMutation;
+use crate::ObjectMutation;
+use crate::StagingMutation;
+use crate::VersionPartMutation;
+use crate::error::DbRes;
+
+const BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Display)]
+pub enum FsckIssue {
+    #[display("object {object_id} references version {version_id} of another object")]
+    ObjectVersion { object_id: Uuid, version_id: Uuid },
+
+    #[display("delete marker {version_id} has parts")]
+    DeleteMarkerParts { version_id: Uuid },
+
+    #[display("inline version {version_id} has parts")]
+    InlineParts { version_id: Uuid },
+
+    #[display("version {version_id} has {actual} parts, expected {expected}")]
+    PartsCount {
+        version_id: Uuid,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[display("version {version_id} parts do not tile its size at part {version_part_id}")]
+    VersionPartRange {
+        version_id: Uuid,
+        version_part_id: Uuid,
+    },
+
+    #[display("version {version_id} part {version_part_id} chunks do not tile its size")]
+    VersionPartChunks {
+        version_id: Uuid,
+        version_part_id: Uuid,
+    },
+
+    #[display("upload {upload_id} part {upload_part_id} chunks do not tile its size")]
+    UploadPartChunks {
+        upload_id: Uuid,
+        upload_part_id: Uuid,
+    },
+
+    #[display("{count} staged chunks")]
+    StagedChunks { count: u64 },
+
+    #[display("tag set {tag_set_id} has no owner")]
+    OrphanedTagSet { tag_set_id: Uuid },
+}
+
+fn chunks_tile(size: i64, chunks: &[(Uuid, i64, i64)]) -> bool {
+    let mut offset = 0;
+    for &(_, start, end) in chunks {
+        if start != offset || end < start {
+            return false;
+        }
+        offset = end + 1;
+    }
+
+    offset == size
+}
+
+fn parts_tile(size: i64, parts: &[version_part::Model]) -> Result<(), Uuid> {
+    let mut offset = 0;
+    for (index, part) in parts.iter().enumerate() {
+        if part.number as usize != index + 1
+            || part.start != offset
+            || part.size < 0
+            || part.end != (offset + part.size - 1).max(0)
+        {
+            return Err(part.id);
+        }
+        offset += part.size;
+    }
+
+    match parts.last() {
+        Some(part) if offset != size => Err(part.id),
+        _ => Ok(()),
+    }
+}
+
+pub struct FsckQuery;
+
+impl FsckQuery {
+    async fn find_many_object_version(db: &impl ConnectionTrait) -> DbRes<Vec<FsckIssue>> {
+        let objects: Vec<(Uuid, Uuid)> = Object::find()
+            .select_only()
+            .columns([object::Column::Id, object::Column::VersionId])
+            .filter(
+                object::Column::VersionId.not_in_subquery(
+                    Query::select()
+                        .column((Version, version::Column::Id))
+                        .from(Version)
+                        .and_where(
+                            Expr::col((Version, version::Column::ObjectId))
+                                .equals((Object, object::Column::Id)),
+                        )
+                        .to_owned(),
+                ),
+            )
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(objects
+            .into_iter()
+            .map(|(object_id, version_id)| FsckIssue::ObjectVersion {
+                object_id,
+                version_id,
+            })
+            .collect())
+    }
+
+    async fn check_version(
+        db: &impl ConnectionTrait,
+        version: &version::Model,
+        issues: &mut Vec<FsckIssue>,
+    ) -> DbRes<()> {
+        let parts = VersionPart::find()
+            .filter(version_part::Column::VersionId.eq(version.id))
+            .order_by_asc(version_part::Column::Number)
+            .all(db)
+            .await?;
+
+        let Some(parts_count) = version.parts_count else {
+            if !parts.is_empty() {
+                issues.push(FsckIssue::DeleteMarkerParts {
+                    version_id: version.id,
+                });
+            }
+
+            return Ok(());
+        };
+        if version.data.is_some() || version.path.is_some() {
+            if !parts.is_empty() {
+                issues.push(FsckIssue::InlineParts {
+                    version_id: version.id,
+                });
+            }
+
+            return Ok(());
+        }
+
+        let expected = (parts_count as usize).max(1);
+        if parts.len() != expected {
+            issues.push(FsckIssue::PartsCount {
+                version_id: version.id,
+                expected,
+                actual: parts.len(),
+            });
+        } else if let Err(version_part_id) = parts_tile(version.size.unwrap_or_default(), &parts) {
+            issues.push(FsckIssue::VersionPartRange {
+                version_id: version.id,
+                version_part_id,
+            });
+        }
+
+        for part in &parts {
+            let chunks = ChunkQuery::find_many_ranged_by_version_part_id(db, part.id, None).await?;
+            if !chunks_tile(part.size, &chunks) {
+                issues.push(FsckIssue::VersionPartChunks {
+                    version_id: version.id,
+                    version_part_id: part.id,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn find_many_version(db: &impl ConnectionTrait) -> DbRes<Vec<FsckIssue>> {
+        let mut issues = vec![];
+
+        let mut after = None;
+        loop {
+            let versions = Version::find()
+                .apply_if(after, |query, after| {
+                    query.filter(version::Column::Id.gt(after))
+                })
+                .order_by_asc(version::Column::Id)
+                .limit(BATCH_SIZE)
+                .all(db)
+                .await?;
+            let Some(last) = versions.last() else {
+                break;
+            };
+            after = Some(last.id);
+
+            for version in &versions {
+                Self::check_version(db, version, &mut issues).await?;
+            }
+        }
+
+        Ok(issues)
+    }
+
+    async fn find_many_upload_part(db: &impl ConnectionTrait) -> DbRes<Vec<FsckIssue>> {
+        let mut issues = vec![];
+
+        let mut after = None;
+        loop {
+            let parts = UploadPart::find()
+                .apply_if(after, |query, after| {
+                    query.filter(upload_part::Column::Id.gt(after))
+                })
+                .order_by_asc(upload_part::Column::Id)
+                .limit(BATCH_SIZE)
+                .all(db)
+                .await?;
+            let Some(last) = parts.last() else {
+                break;
+            };
+            after = Some(last.id);
+
+            for part in &parts {
+                let chunks = ChunkQuery::find_many_by_upload_part_id(db, part.id).await?;
+                if !chunks_tile(part.size, &chunks) {
+                    issues.push(FsckIssue::UploadPartChunks {
+                        upload_id: part.upload_id,
+                        upload_part_id: part.id,
+                    });
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+
+    async fn find_many_orphaned_tag_set(db: &impl ConnectionTrait) -> DbRes<Vec<FsckIssue>> {
+        let ids: Vec<Uuid> = TagSet::find()
+            .select_only()
+            .column(tag_set::Column::Id)
+            .filter(tag_set::Column::BucketId.is_null())
+            .filter(tag_set::Column::UploadId.is_null())
+            .filter(tag_set::Column::VersionId.is_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(ids
+            .into_iter()
+            .map(|tag_set_id| FsckIssue::OrphanedTagSet { tag_set_id })
+            .collect())
+    }
+
+    pub async fn check(db: &impl ConnectionTrait) -> DbRes<Vec<FsckIssue>> {
+        let mut issues = Self::find_many_object_version(db).await?;
+        issues.extend(Self::find_many_version(db).await?);
+        issues.extend(Self::find_many_upload_part(db).await?);
+
+        let count = ChunkQuery::count_staged(db).await?;
+        if count > 0 {
+            issues.push(FsckIssue::StagedChunks { count });
+        }
+
+        issues.extend(Self::find_many_orphaned_tag_set(db).await?);
+
+        Ok(issues)
+    }
+}
+
+pub struct FsckMutation;
+
+impl FsckMutation {
+    pub async fn repair(
+        db: &(impl ConnectionTrait + StreamTrait),
+        issue: &FsckIssue,
+        staged_before: SystemTime,
+    ) -> DbRes<bool> {
+        match *issue {
+            FsckIssue::ObjectVersion { object_id, .. } => {
+                let version_id: Option<Uuid> = Version::find()
+                    .select_only()
+                    .column(version::Column::Id)
+                    .filter(version::Column::ObjectId.eq(object_id))
+                    .order_by_desc(version::Column::CreatedAt)
+                    .into_tuple()
+                    .one(db)
+                    .await?;
+
+                if let Some(version_id) = version_id {
+                    ObjectMutation::update_version_id(db, object_id, version_id).await?;
+                } else {
+                    Object::delete_by_id(object_id).exec(db).await?;
+                }
+            }
+            FsckIssue::DeleteMarkerParts { version_id } | FsckIssue::InlineParts { version_id } => {
+                VersionPartMutation::delete_many(db, version_id).await?;
+            }
+            FsckIssue::PartsCount { version_id, .. } => {
+                CorruptVersionMutation::upsert(db, version_id, None, issue.to_string()).await?;
+            }
+            FsckIssue::VersionPartRange {
+                version_id,
+                version_part_id,
+            }
+            | FsckIssue::VersionPartChunks {
+                version_id,
+                version_part_id,
+            } => {
+                CorruptVersionMutation::upsert(
+                    db,
+                    version_id,
+                    Some(version_part_id),
+                    issue.to_string(),
+                )
+                .await?;
+            }
+            FsckIssue::UploadPartChunks { .. } => return Ok(false),
+            FsckIssue::StagedChunks { .. } => {
+                StagingMutation::delete_many_expired(db, staged_before.into()).await?;
+            }
+            FsckIssue::OrphanedTagSet { tag_set_id } => {
+                TagSet::delete_by_id(tag_set_id).exec(db).await?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::utils::test_db;
+
+    fn part(number: i16, start: i64, end: i64, size: i64) -> version_part::Model {
+        version_part::Model {
+            id: Uuid::new_v4(),
+            version_id: Uuid::nil(),
+            number,
+            start,
+            end,
+            size,
+            crc32: vec![],
+            crc32_c: vec![],
+            crc64_nvme: vec![],
+            sha1: vec![],
+            sha256: vec![],
+            md5: vec![],
+            created_at: DateTimeUtc::default(),
+        }
+    }
+
+    #[test]
+    fn test_chunks_tile() {
+        let id = Uuid::nil();
+
+        assert!(chunks_tile(0, &[]));
+        assert!(chunks_tile(10, &[(id, 0, 3), (id, 4, 9)]));
+        assert!(!chunks_tile(10, &[(id, 0, 3), (id, 5, 9)]));
+        assert!(!chunks_tile(10, &[(id, 0, 3), (id, 4, 8)]));
+        assert!(!chunks_tile(10, &[(id, 1, 9)]));
+    }
+
+    #[test]
+    fn test_parts_tile() {
+        assert!(parts_tile(0, &[part(1, 0, 0, 0)]).is_ok());
+        assert!(parts_tile(10, &[part(1, 0, 3, 4), part(2, 4, 9, 6)]).is_ok());
+        assert!(parts_tile(10, &[part(1, 0, 9, 10), part(2, 10, 9, 0)]).is_ok());
+        assert!(parts_tile(10, &[part(1, 0, 3, 4), part(3, 4, 9, 6)]).is_err());
+        assert!(parts_tile(10, &[part(1, 0, 3, 4), part(2, 5, 9, 5)]).is_err());
+        assert!(parts_tile(11, &[part(1, 0, 3, 4), part(2, 4, 9, 6)]).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_repair_staged_chunks() {
+        let db_conn = test_db::connect().await;
+        let id = StagingMutation::insert(&db_conn).await.unwrap();
+        let issue = FsckIssue::StagedChunks { count: 1 };
+
+        let before = SystemTime::now() - Duration::from_mins(1);
+        FsckMutation::repair(&db_conn, &issue, before)
+            .await
+            .unwrap();
+        StagingMutation::lock(&db_conn, id).await.unwrap();
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let before = SystemTime::now() - Duration::from_secs(1);
+        FsckMutation::repair(&db_conn, &issue, before)
+            .await
+            .unwrap();
+        assert!(StagingMutation::lock(&db_conn, id).await.is_err());
+    }
+}
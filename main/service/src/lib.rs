@@ -2,9 +2,15 @@ pub mod prelude;
 
 mod bucket;
 mod chunk;
+mod chunk_cache;
+mod corrupt_version;
 mod error;
+mod fsck;
+mod mount;
 mod object;
 mod owner;
+mod staging;
+mod storage;
 mod tag;
 mod tag_set;
 mod upload;
@@ -13,6 +19,13 @@ mod utils;
 mod version;
 mod version_part;
 
+pub use chunk::StagedChunks;
+pub use chunk_cache::ChunkCache;
+pub use chunk_cache::ChunkCacheStats;
+pub use corrupt_version::CorruptVersionObject;
 pub use error::InsErr;
 pub use error::InsRes;
+pub use fsck::FsckIssue;
+pub use minil_entity::sea_orm_active_enums::ChunkTier;
 pub use prelude::*;
+pub use storage::ChunkStorage;
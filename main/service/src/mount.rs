@@ -0,0 +1,191 @@
+use std::fs::Metadata;
+use std::io;
+use std::io::SeekFrom;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+
+use async_stream::try_stream;
+use bytes::Bytes;
+use bytesize::ByteSize;
+use futures::Stream;
+use futures::StreamExt;
+use mime::Mime;
+use tokio::fs;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio_util::io::ReaderStream;
+
+use crate::InsRes;
+use crate::utils::Checksum;
+use crate::utils::ChecksumDigest;
+use crate::utils::get_mime;
+
+pub struct MountQuery;
+
+impl MountQuery {
+    pub async fn find_many_files(root: &Path) -> io::Result<Vec<(String, PathBuf, Metadata)>> {
+        let root = fs::canonicalize(root).await?;
+
+        let mut files = vec![];
+        let mut dirs = vec![root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = fs::read_dir(dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+
+                let Ok(metadata) = fs::metadata(&path).await else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                let Some(key) = path
+                    .strip_prefix(&root)
+                    .unwrap()
+                    .iter()
+                    .map(|component| component.to_str())
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+                files.push((key.join("/"), path, metadata));
+            }
+        }
+        files.sort_unstable_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+        Ok(files)
+    }
+
+    pub(super) async fn find_checksum(
+        key: &str,
+        path: &Path,
+    ) -> io::Result<(Checksum, Option<Mime>)> {
+        let mut file = File::open(path).await?;
+        let mut digest = ChecksumDigest::new();
+
+        let mut data = vec![];
+        (&mut file)
+            .take(ByteSize::kib(4).as_u64())
+            .read_to_end(&mut data)
+            .await?;
+        let mime = get_mime(key, &data);
+        digest.update(&data);
+
+        data.resize(ByteSize::kib(64).as_u64() as usize, 0);
+        loop {
+            let len = file.read(&mut data).await?;
+            if len == 0 {
+                break;
+            }
+            digest.update(&data[..len]);
+        }
+
+        Ok((digest.finalize(), mime))
+    }
+
+    pub fn find_ranged_data(
+        path: String,
+        size: u64,
+        range: Option<RangeInclusive<u64>>,
+    ) -> impl Stream<Item = InsRes<Bytes>> {
+        let (start, len) = range.map_or((0, size), |range| {
+            (*range.start(), range.end() - range.start() + 1)
+        });
+
+        try_stream! {
+            let mut file = File::open(path).await?;
+            file.seek(SeekFrom::Start(start)).await?;
+
+            let mut stream = ReaderStream::new(file.take(len));
+            while let Some(data) = stream.next().await {
+                yield data?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/b/c.txt"), "hello").unwrap();
+        std::fs::write(dir.path().join("a/d.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("e"), "0123456789").unwrap();
+
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_find_many_files() {
+        let dir = tree();
+
+        let files = MountQuery::find_many_files(dir.path()).await.unwrap();
+        let keys = files
+            .iter()
+            .map(|(key, _, _)| key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["a/b/c.txt", "a/d.json", "e"]);
+        assert!(files[0].1.ends_with("a/b/c.txt"));
+        assert_eq!(files[0].2.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_find_many_files_missing() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(
+            MountQuery::find_many_files(&dir.path().join("missing"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_checksum() {
+        let dir = tree();
+
+        let (checksum, mime) =
+            MountQuery::find_checksum("a/b/c.txt", &dir.path().join("a/b/c.txt"))
+                .await
+                .unwrap();
+        assert_eq!(checksum.size, 5);
+        assert_eq!(
+            hex::encode(checksum.md5),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+        assert_eq!(mime, Some(mime::TEXT_PLAIN));
+    }
+
+    #[tokio::test]
+    async fn test_find_ranged_data() {
+        let dir = tree();
+        let path = dir.path().join("e").to_str().unwrap().to_owned();
+
+        let data = MountQuery::find_ranged_data(path.clone(), 10, None)
+            .map_ok(Vec::from)
+            .try_concat()
+            .await
+            .unwrap();
+        assert_eq!(data, b"0123456789"[..]);
+
+        let data = MountQuery::find_ranged_data(path, 10, Some(2..=5))
+            .map_ok(Vec::from)
+            .try_concat()
+            .await
+            .unwrap();
+        assert_eq!(data, b"2345"[..]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::io;
+use std::path::Path;
 use std::pin::pin;
 
 use bytes::Bytes;
@@ -10,6 +12,7 @@ use minil_entity::object;
 use minil_entity::prelude::*;
 use minil_entity::upload_part;
 use minil_entity::version;
+use minil_entity::version_part;
 use sea_orm::prelude::*;
 use sea_orm::*;
 use sea_orm_ext::prelude::*;
@@ -20,6 +23,8 @@ use tokio_util::io::StreamReader;
 use uuid::Uuid;
 
 use crate::InsRes;
+use crate::MountQuery;
+use crate::StagedChunks;
 use crate::VersionMutation;
 use crate::VersionQuery;
 use crate::error::DbRes;
@@ -83,6 +88,21 @@ impl ObjectQuery {
             .await
     }
 
+    pub async fn find_both_latest_version_for_update(
+        db: &impl ConnectionTrait,
+        bucket_id: Uuid,
+        key: &str,
+    ) -> DbRes<Option<(object::Model, version::Model)>> {
+        Object::find()
+            .join(JoinType::InnerJoin, object::Relation::LatestVersion.def())
+            .select_also(Version)
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(key))
+            .lock_exclusive()
+            .one_both(db)
+            .await
+    }
+
     pub async fn find_many_both_latest_version(
         db: &(impl ConnectionTrait + StreamTrait),
         bucket_id: Uuid,
@@ -134,7 +154,7 @@ impl ObjectMutation {
         Ok((object, version))
     }
 
-    async fn update_version_id(
+    pub(super) async fn update_version_id(
         db: &(impl ConnectionTrait + StreamTrait),
         id: Uuid,
         version_id: Uuid,
@@ -154,6 +174,28 @@ impl ObjectMutation {
             .await
     }
 
+    pub async fn update_key(
+        db: &(impl ConnectionTrait + StreamTrait),
+        bucket_id: Uuid,
+        key: &str,
+        new_key: String,
+    ) -> DbRes<Option<object::Model>> {
+        let object = object::ActiveModel {
+            key: Set(new_key),
+            ..Default::default()
+        };
+
+        Object::update_many()
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(key))
+            .set(object)
+            .col_expr(object::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
     pub async fn update_also_delete_marker(
         db: &(impl ConnectionTrait + StreamTrait),
         bucket_id: Uuid,
@@ -179,12 +221,17 @@ impl ObjectMutation {
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn upsert_also_version(
         db: &(impl ConnectionTrait + StreamTrait),
+        db_conn: &impl ConnectionTrait,
         bucket_id: Uuid,
         key: String,
         versioning: bool,
         mut mime: Option<&Mime>,
+        storage_class: Option<&str>,
+        chunk_size: ByteSize,
+        inline_threshold: ByteSize,
         read: impl AsyncRead,
     ) -> InsRes<(object::Model, version::Model)> {
         let (id, version_id) =
@@ -217,9 +264,19 @@ impl ObjectMutation {
         }
 
         let read = StreamReader::new(stream);
-        let version =
-            VersionMutation::upsert_version_also_part(db, version_id, id, versioning, mime, read)
-                .await?;
+        let version = VersionMutation::upsert_version_also_part(
+            db,
+            db_conn,
+            version_id,
+            id,
+            versioning,
+            mime,
+            storage_class,
+            chunk_size,
+            inline_threshold,
+            read,
+        )
+        .await?;
 
         let object = object::ActiveModel {
             id: Set(id),
@@ -260,6 +317,7 @@ impl ObjectMutation {
         key: String,
         versioning: bool,
         mime: Option<&Mime>,
+        storage_class: Option<&str>,
         iter: impl Iterator<Item = upload_part::Model>,
     ) -> DbRes<(object::Model, version::Model)> {
         let (id, version_id) =
@@ -273,7 +331,66 @@ impl ObjectMutation {
             };
 
         let version = VersionMutation::upsert_version_with_part_from_upload_parts(
-            db, version_id, id, versioning, mime, iter,
+            db,
+            version_id,
+            id,
+            versioning,
+            mime,
+            storage_class,
+            iter,
+        )
+        .await?;
+
+        let object = object::ActiveModel {
+            id: Set(id),
+            bucket_id: Set(bucket_id),
+            key: Set(key),
+            version_id: Set(version.id),
+            ..Default::default()
+        };
+
+        let object = Object::insert(object)
+            .on_conflict(
+                OnConflict::columns([object::Column::BucketId, object::Column::Key])
+                    .update_column(object::Column::VersionId)
+                    .value(object::Column::UpdatedAt, Expr::current_timestamp())
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await?;
+
+        Ok((object, version))
+    }
+
+    pub async fn upsert_mounted_also_version(
+        db: &(impl ConnectionTrait + StreamTrait),
+        bucket_id: Uuid,
+        key: String,
+        path: &Path,
+        modified: DateTimeUtc,
+    ) -> InsRes<(object::Model, version::Model)> {
+        let (id, version_id) =
+            match ObjectQuery::find_both_latest_version(db, bucket_id, &key).await? {
+                Some((object, version)) => {
+                    let version_id = (!version.versioning).then_some(version.id);
+
+                    (object.id, version_id)
+                }
+                None => (Uuid::new_v4(), None),
+            };
+
+        let (checksum, mime) = MountQuery::find_checksum(&key, path).await?;
+        let path = path
+            .to_str()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidFilename))?;
+        let version = VersionMutation::upsert_mounted_also_part(
+            db,
+            version_id,
+            id,
+            mime.as_ref(),
+            path.to_owned(),
+            checksum,
+            modified,
         )
         .await?;
 
@@ -298,6 +415,60 @@ impl ObjectMutation {
         Ok((object, version))
     }
 
+    pub async fn upsert_imported_also_version(
+        db: &impl ConnectionTrait,
+        bucket_id: Uuid,
+        key: String,
+        mut version: version::Model,
+        parts: Vec<(version_part::Model, StagedChunks)>,
+        latest: bool,
+    ) -> InsRes<(object::Model, version::Model)> {
+        let object = Object::find()
+            .filter(object::Column::BucketId.eq(bucket_id))
+            .filter(object::Column::Key.eq(&key))
+            .one(db)
+            .await?;
+
+        version.object_id = object
+            .as_ref()
+            .map_or_else(Uuid::new_v4, |object| object.id);
+        if let Some(existing) = Version::find_by_id(version.id).one(db).await?
+            && existing.object_id != version.object_id
+        {
+            Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("version {} belongs to another object", version.id),
+            ))?;
+        }
+
+        let version = VersionMutation::upsert_imported_also_part(db, version, parts).await?;
+
+        let object = match object {
+            Some(object) if !latest => object,
+            _ => {
+                let object = object::ActiveModel {
+                    id: Set(version.object_id),
+                    bucket_id: Set(bucket_id),
+                    key: Set(key),
+                    version_id: Set(version.id),
+                    ..Default::default()
+                };
+
+                Object::insert(object)
+                    .on_conflict(
+                        OnConflict::columns([object::Column::BucketId, object::Column::Key])
+                            .update_column(object::Column::VersionId)
+                            .value(object::Column::UpdatedAt, Expr::current_timestamp())
+                            .to_owned(),
+                    )
+                    .exec_with_returning(db)
+                    .await?
+            }
+        };
+
+        Ok((object, version))
+    }
+
     pub async fn delete(
         db: &(impl ConnectionTrait + StreamTrait),
         bucket_id: Uuid,
@@ -343,3 +514,99 @@ impl ObjectMutation {
         Ok(object_version)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VersionPartQuery;
+    use crate::utils::test_db;
+
+    async fn upsert(db_conn: &DbConn, bucket_id: Uuid, key: &str, size: usize) -> version::Model {
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &db_txn,
+            db_conn,
+            bucket_id,
+            key.to_owned(),
+            false,
+            None,
+            None,
+            ByteSize::kib(1),
+            ByteSize::kib(4),
+            vec![b'a'; size].as_slice(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        version
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_upsert_also_version_inline() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+
+        let version = upsert(&db_conn, bucket.id, "small", 4095).await;
+        assert_eq!(version.data.as_deref(), Some(&[b'a'; 4095][..]));
+        assert_eq!(version.size, Some(4095));
+        assert!(
+            VersionPartQuery::find(&db_conn, version.id, 1)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let version = upsert(&db_conn, bucket.id, "large", 4096).await;
+        assert_eq!(version.data, None);
+        assert_eq!(version.size, Some(4096));
+        assert!(
+            VersionPartQuery::find(&db_conn, version.id, 1)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_upsert_mounted_also_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mounted.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_mounted_also_version(
+            &db_txn,
+            bucket.id,
+            "mounted.txt".to_owned(),
+            &path,
+            DateTimeUtc::default(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+        assert_eq!(version.path.as_deref(), path.to_str());
+        assert_eq!(version.size, Some(5));
+        assert_eq!(version.mime.as_deref(), Some("text/plain"));
+        assert_eq!(version.data, None);
+
+        std::fs::write(&path, "hello world").unwrap();
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, remounted) = ObjectMutation::upsert_mounted_also_version(
+            &db_txn,
+            bucket.id,
+            "mounted.txt".to_owned(),
+            &path,
+            DateTimeUtc::default(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+        assert_eq!(remounted.id, version.id);
+        assert_eq!(remounted.size, Some(11));
+    }
+}
@@ -1,6 +1,12 @@
+use futures::TryStreamExt;
 use minil_entity::owner;
 use minil_entity::prelude::*;
 use sea_orm::*;
+use sea_orm_ext::prelude::*;
+use sea_query::Expr;
+use sea_query::LockType;
+use sea_query::OnConflict;
+use uuid::Uuid;
 
 use crate::error::DbRes;
 
@@ -13,8 +19,165 @@ impl OwnerQuery {
             .one(db)
             .await
     }
+
+    pub async fn find_for_update(
+        db: &impl ConnectionTrait,
+        id: Uuid,
+    ) -> DbRes<Option<owner::Model>> {
+        Owner::find_by_id(id)
+            .lock(LockType::NoKeyUpdate)
+            .one(db)
+            .await
+    }
+
+    pub async fn find_many(db: &impl ConnectionTrait) -> DbRes<Vec<owner::Model>> {
+        Owner::find()
+            .order_by_asc(owner::Column::Name)
+            .all(db)
+            .await
+    }
 }
 
 pub struct OwnerMutation;
 
-impl OwnerMutation {}
+impl OwnerMutation {
+    pub async fn insert(db: &impl ConnectionTrait, name: String) -> DbRes<Option<owner::Model>> {
+        let (access_key, secret_key) = generate_keys();
+        let owner = owner::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(name),
+            access_key: Set(Some(access_key)),
+            secret_key: Set(Some(secret_key)),
+            ..Default::default()
+        };
+
+        Owner::insert(owner)
+            .on_conflict(
+                OnConflict::column(owner::Column::Name)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await
+            .map(Some)
+            .or_else(|err| match err {
+                DbErr::RecordNotFound(_) | DbErr::RecordNotInserted => Ok(None),
+                _ => Err(err),
+            })
+    }
+
+    pub async fn update_keys(
+        db: &(impl ConnectionTrait + StreamTrait),
+        name: &str,
+    ) -> DbRes<Option<owner::Model>> {
+        let (access_key, secret_key) = generate_keys();
+        let owner = owner::ActiveModel {
+            access_key: Set(Some(access_key)),
+            secret_key: Set(Some(secret_key)),
+            ..Default::default()
+        };
+
+        Owner::update_many()
+            .filter(owner::Column::Name.eq(name))
+            .set(owner)
+            .col_expr(owner::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
+    pub async fn delete_keys(
+        db: &(impl ConnectionTrait + StreamTrait),
+        name: &str,
+    ) -> DbRes<Option<owner::Model>> {
+        let owner = owner::ActiveModel {
+            access_key: Set(None),
+            secret_key: Set(None),
+            ..Default::default()
+        };
+
+        Owner::update_many()
+            .filter(owner::Column::Name.eq(name))
+            .set(owner)
+            .col_expr(owner::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
+    pub async fn update_disabled(
+        db: &(impl ConnectionTrait + StreamTrait),
+        name: &str,
+        disabled: bool,
+    ) -> DbRes<Option<owner::Model>> {
+        let owner = owner::ActiveModel {
+            disabled: Set(disabled),
+            ..Default::default()
+        };
+
+        Owner::update_many()
+            .filter(owner::Column::Name.eq(name))
+            .set(owner)
+            .col_expr(owner::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+
+    pub async fn update_quota(
+        db: &(impl ConnectionTrait + StreamTrait),
+        name: &str,
+        size: Option<i64>,
+        objects: Option<i64>,
+    ) -> DbRes<Option<owner::Model>> {
+        let owner = owner::ActiveModel {
+            quota_size: Set(size),
+            quota_objects: Set(objects),
+            ..Default::default()
+        };
+
+        Owner::update_many()
+            .filter(owner::Column::Name.eq(name))
+            .set(owner)
+            .col_expr(owner::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await
+    }
+}
+
+fn generate_keys() -> (String, String) {
+    let access_key = Uuid::new_v4().simple().to_string().to_uppercase();
+    let secret_key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+    (access_key[..20].to_owned(), secret_key[..40].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_db;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_update_quota() {
+        let db_conn = test_db::connect().await;
+        let (owner, _) = test_db::create_bucket(&db_conn).await;
+        assert_eq!(owner.updated_at, None);
+
+        let owner = OwnerMutation::update_quota(&db_conn, &owner.name, Some(10), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.quota_size, Some(10));
+        assert_eq!(owner.quota_objects, None);
+        assert!(owner.updated_at.is_some());
+        assert!(owner.exceeds_quota(11, 0));
+        assert!(!owner.exceeds_quota(10, 1));
+        assert!(!owner.exceeds_quota(-1, 0));
+    }
+}
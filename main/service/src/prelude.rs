@@ -2,10 +2,16 @@ pub use super::bucket::BucketMutation;
 pub use super::bucket::BucketQuery;
 pub use super::chunk::ChunkMutation;
 pub use super::chunk::ChunkQuery;
+pub use super::corrupt_version::CorruptVersionMutation;
+pub use super::corrupt_version::CorruptVersionQuery;
+pub use super::fsck::FsckMutation;
+pub use super::fsck::FsckQuery;
+pub use super::mount::MountQuery;
 pub use super::object::ObjectMutation;
 pub use super::object::ObjectQuery;
 pub use super::owner::OwnerMutation;
 pub use super::owner::OwnerQuery;
+pub use super::staging::StagingMutation;
 pub use super::tag::TagMutation;
 pub use super::tag::TagQuery;
 pub use super::tag_set::TagSetMutation;
@@ -0,0 +1,64 @@
+use minil_entity::prelude::*;
+use minil_entity::staging;
+use sea_orm::prelude::*;
+use sea_orm::*;
+use sea_query::*;
+
+use crate::error::DbRes;
+
+pub struct StagingMutation;
+
+impl StagingMutation {
+    pub(super) async fn insert(db: &impl ConnectionTrait) -> DbRes<Uuid> {
+        let staging = staging::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            ..Default::default()
+        };
+
+        Ok(Staging::insert(staging).exec(db).await?.last_insert_id)
+    }
+
+    pub(super) async fn touch(db: &impl ConnectionTrait, id: Uuid) -> DbRes<()> {
+        let res = Staging::update_many()
+            .filter(staging::Column::Id.eq(id))
+            .col_expr(staging::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec(db)
+            .await?;
+
+        if res.rows_affected == 0 {
+            Err(DbErr::RecordNotFound(format!("staging {id}")))?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) async fn lock(db: &impl ConnectionTrait, id: Uuid) -> DbRes<()> {
+        Staging::find_by_id(id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("staging {id}")))?;
+
+        Ok(())
+    }
+
+    pub(super) async fn delete(db: &impl ConnectionTrait, id: Uuid) -> DbRes<DeleteResult> {
+        Staging::delete_by_id(id).exec(db).await
+    }
+
+    pub async fn delete_many_expired(
+        db: &impl ConnectionTrait,
+        before: DateTimeUtc,
+    ) -> DbRes<DeleteResult> {
+        Staging::delete_many()
+            .filter(
+                Expr::expr(Func::coalesce([
+                    Expr::col(staging::Column::UpdatedAt).into(),
+                    Expr::col(staging::Column::CreatedAt).into(),
+                ]))
+                .lt(before),
+            )
+            .exec(db)
+            .await
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use crc_fast::CrcAlgorithm;
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use tokio::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::storage::list_files;
+use crate::storage::remove_file;
+use crate::storage::write_file;
+
+const HEADER_LEN: usize = 12;
+
+#[derive(Debug)]
+pub(crate) struct ErasureBackend {
+    roots: Vec<PathBuf>,
+    codec: ReedSolomon,
+}
+
+impl ErasureBackend {
+    pub(crate) fn new(
+        roots: Vec<PathBuf>,
+        data_shards: usize,
+        parity_shards: usize,
+    ) -> io::Result<Self> {
+        if roots.len() < data_shards + parity_shards {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not enough directories for data and parity shards",
+            ))?;
+        }
+
+        let codec = ReedSolomon::new(data_shards, parity_shards)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        Ok(Self { roots, codec })
+    }
+
+    fn paths(&self, id: Uuid) -> impl Iterator<Item = PathBuf> {
+        let offset = (id.as_u128() % self.roots.len() as u128) as usize;
+        let id = id.simple().to_string();
+
+        (0..self.codec.total_shard_count()).map(move |index| {
+            self.roots[(offset + index) % self.roots.len()]
+                .join(&id[..2])
+                .join(format!("{id}.{index}"))
+        })
+    }
+
+    pub(crate) async fn read(&self, id: Uuid) -> io::Result<(Bytes, usize)> {
+        let mut len = None;
+        let mut shards = vec![];
+        let mut error = None;
+        for path in self.paths(id) {
+            let shard = match fs::read(&path).await {
+                Ok(shard) => shard,
+                Err(err) => {
+                    if err.kind() != io::ErrorKind::NotFound {
+                        warn!(path = %path.display(), %err, "failed to read shard");
+                        error = Some(err);
+                    }
+                    shards.push(None);
+                    continue;
+                }
+            };
+
+            shards.push(
+                shard
+                    .split_at_checked(HEADER_LEN)
+                    .and_then(|(header, shard)| {
+                        let (shard_len, crc32) = header.split_at(8);
+                        let crc32 = u32::from_be_bytes(crc32.try_into().unwrap());
+                        (crc_fast::checksum(CrcAlgorithm::Crc32IsoHdlc, shard) == u64::from(crc32))
+                            .then(|| {
+                                len = Some(
+                                    u64::from_be_bytes(shard_len.try_into().unwrap()) as usize
+                                );
+                                shard.to_vec()
+                            })
+                    }),
+            );
+        }
+
+        let lost = shards.iter().filter(|shard| shard.is_none()).count();
+        if let Some(err) = error
+            && shards.len() - lost < self.codec.data_shard_count()
+        {
+            Err(err)?;
+        }
+        let len = len.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no shards for chunk {id}"))
+        })?;
+        if lost > 0 {
+            self.codec
+                .reconstruct_data(&mut shards)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+
+        let mut data = shards
+            .into_iter()
+            .take(self.codec.data_shard_count())
+            .flat_map(Option::unwrap)
+            .collect::<Vec<_>>();
+        data.truncate(len);
+
+        Ok((Bytes::from(data), lost))
+    }
+
+    pub(crate) async fn write(&self, id: Uuid, data: &[u8]) -> io::Result<()> {
+        let shard_len = data.len().div_ceil(self.codec.data_shard_count()).max(1);
+
+        let mut shards = data
+            .chunks(shard_len)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        shards.resize(self.codec.total_shard_count(), vec![]);
+        for shard in &mut shards {
+            shard.resize(shard_len, 0);
+        }
+        self.codec
+            .encode(&mut shards)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        for (path, shard) in self.paths(id).zip(shards) {
+            let crc32 = crc_fast::checksum(CrcAlgorithm::Crc32IsoHdlc, &shard) as u32;
+
+            let mut buf = Vec::with_capacity(HEADER_LEN + shard.len());
+            buf.extend_from_slice(&(data.len() as u64).to_be_bytes());
+            buf.extend_from_slice(&crc32.to_be_bytes());
+            buf.extend_from_slice(&shard);
+            write_file(&path, &buf).await?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn delete(&self, id: Uuid) -> io::Result<()> {
+        for path in self.paths(id) {
+            remove_file(&path).await?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn list(&self) -> io::Result<Vec<(Uuid, SystemTime)>> {
+        let mut ids = HashMap::<Uuid, SystemTime>::new();
+
+        for root in &self.roots {
+            for (name, modified) in list_files(root).await? {
+                let Some(id) = name
+                    .split_once('.')
+                    .and_then(|(id, _)| Uuid::try_parse(id).ok())
+                else {
+                    continue;
+                };
+
+                ids.entry(id)
+                    .and_modify(|latest| *latest = (*latest).max(modified))
+                    .or_insert(modified);
+            }
+        }
+
+        Ok(ids.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn backend(dir: &TempDir) -> ErasureBackend {
+        let roots = (0..6).map(|index| dir.path().join(format!("disk{index}")));
+        ErasureBackend::new(roots.collect(), 4, 2).unwrap()
+    }
+
+    fn data() -> Vec<u8> {
+        (0..10_000u32).map(|index| index as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_read() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &data()).await.unwrap();
+
+        let (read, lost) = backend.read(id).await.unwrap();
+        assert_eq!(read, data());
+        assert_eq!(lost, 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_lost_disks() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &data()).await.unwrap();
+        fs::remove_dir_all(&backend.roots[0]).await.unwrap();
+        fs::remove_dir_all(&backend.roots[3]).await.unwrap();
+
+        let (read, lost) = backend.read(id).await.unwrap();
+        assert_eq!(read, data());
+        assert_eq!(lost, 2);
+    }
+
+    #[tokio::test]
+    async fn test_read_corrupt_shard() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &data()).await.unwrap();
+        let path = backend.paths(id).next().unwrap();
+        let mut shard = fs::read(&path).await.unwrap();
+        shard[HEADER_LEN] ^= 0xFF;
+        fs::write(&path, shard).await.unwrap();
+
+        let (read, lost) = backend.read(id).await.unwrap();
+        assert_eq!(read, data());
+        assert_eq!(lost, 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_failed_shards() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &data()).await.unwrap();
+        let paths = backend.paths(id).collect::<Vec<_>>();
+        for path in &paths[..2] {
+            fs::remove_file(path).await.unwrap();
+            fs::create_dir(path).await.unwrap();
+        }
+
+        let (read, lost) = backend.read(id).await.unwrap();
+        assert_eq!(read, data());
+        assert_eq!(lost, 2);
+
+        fs::remove_file(&paths[2]).await.unwrap();
+        fs::create_dir(&paths[2]).await.unwrap();
+        assert!(backend.read(id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_too_many_lost_disks() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &data()).await.unwrap();
+        for index in 0..3 {
+            fs::remove_dir_all(&backend.roots[index]).await.unwrap();
+        }
+
+        assert!(backend.read(id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_empty() {
+        let dir = TempDir::new().unwrap();
+        let backend = backend(&dir);
+        let id = Uuid::new_v4();
+
+        backend.write(id, &[]).await.unwrap();
+
+        let (read, lost) = backend.read(id).await.unwrap();
+        assert!(read.is_empty());
+        assert_eq!(lost, 0);
+    }
+}
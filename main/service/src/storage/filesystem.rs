@@ -0,0 +1,47 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::storage::list_files;
+use crate::storage::remove_file;
+use crate::storage::write_file;
+
+#[derive(Debug)]
+pub(crate) struct FilesystemBackend {
+    root: PathBuf,
+}
+
+impl FilesystemBackend {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, id: Uuid) -> PathBuf {
+        let id = id.simple().to_string();
+        self.root.join(&id[..2]).join(id)
+    }
+
+    pub(crate) async fn read(&self, id: Uuid) -> io::Result<Bytes> {
+        fs::read(self.path(id)).await.map(Bytes::from)
+    }
+
+    pub(crate) async fn write(&self, id: Uuid, data: &[u8]) -> io::Result<()> {
+        write_file(&self.path(id), data).await
+    }
+
+    pub(crate) async fn delete(&self, id: Uuid) -> io::Result<()> {
+        remove_file(&self.path(id)).await
+    }
+
+    pub(crate) async fn list(&self) -> io::Result<Vec<(Uuid, SystemTime)>> {
+        Ok(list_files(&self.root)
+            .await?
+            .into_iter()
+            .filter_map(|(name, modified)| Some((Uuid::try_parse(&name).ok()?, modified)))
+            .collect())
+    }
+}
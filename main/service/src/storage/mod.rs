@@ -0,0 +1,174 @@
+mod erasure;
+mod filesystem;
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use minil_entity::sea_orm_active_enums::ChunkTier;
+use parking_lot::Mutex;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::storage::erasure::ErasureBackend;
+use crate::storage::filesystem::FilesystemBackend;
+
+#[derive(Debug)]
+pub struct ChunkStorage {
+    filesystem: Option<FilesystemBackend>,
+    erasure: Option<ErasureBackend>,
+    degraded: Mutex<HashSet<Uuid>>,
+}
+
+impl ChunkStorage {
+    #[must_use]
+    pub fn new(filesystem: Option<PathBuf>) -> Self {
+        Self {
+            filesystem: filesystem.map(FilesystemBackend::new),
+            erasure: None,
+            degraded: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn with_erasure(
+        mut self,
+        roots: Vec<PathBuf>,
+        data_shards: usize,
+        parity_shards: usize,
+    ) -> io::Result<Self> {
+        self.erasure = Some(ErasureBackend::new(roots, data_shards, parity_shards)?);
+
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn tiers(&self) -> Vec<ChunkTier> {
+        [
+            self.filesystem.as_ref().map(|_| ChunkTier::Filesystem),
+            self.erasure.as_ref().map(|_| ChunkTier::Erasure),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    pub fn take_degraded(&self) -> Vec<Uuid> {
+        self.degraded.lock().drain().collect()
+    }
+
+    pub fn extend_degraded(&self, ids: impl IntoIterator<Item = Uuid>) {
+        self.degraded.lock().extend(ids);
+    }
+
+    fn filesystem(&self) -> io::Result<&FilesystemBackend> {
+        self.filesystem
+            .as_ref()
+            .ok_or_else(|| unconfigured(ChunkTier::Filesystem))
+    }
+
+    fn erasure(&self) -> io::Result<&ErasureBackend> {
+        self.erasure
+            .as_ref()
+            .ok_or_else(|| unconfigured(ChunkTier::Erasure))
+    }
+
+    pub async fn probe(&self) -> io::Result<()> {
+        let id = Uuid::new_v4();
+        for tier in self.tiers() {
+            self.write(tier, id, b"probe").await?;
+            self.delete(tier, id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn read(&self, tier: ChunkTier, id: Uuid) -> io::Result<(Bytes, bool)> {
+        match tier {
+            ChunkTier::Database => Err(unconfigured(tier)),
+            ChunkTier::Filesystem => Ok((self.filesystem()?.read(id).await?, false)),
+            ChunkTier::Erasure => {
+                let (data, lost) = self.erasure()?.read(id).await?;
+                Ok((data, lost != 0))
+            }
+        }
+    }
+
+    pub(crate) async fn write(&self, tier: ChunkTier, id: Uuid, data: &[u8]) -> io::Result<()> {
+        match tier {
+            ChunkTier::Database => Err(unconfigured(tier)),
+            ChunkTier::Filesystem => self.filesystem()?.write(id, data).await,
+            ChunkTier::Erasure => self.erasure()?.write(id, data).await,
+        }
+    }
+
+    pub(crate) async fn delete(&self, tier: ChunkTier, id: Uuid) -> io::Result<()> {
+        match tier {
+            ChunkTier::Database => Err(unconfigured(tier)),
+            ChunkTier::Filesystem => self.filesystem()?.delete(id).await,
+            ChunkTier::Erasure => self.erasure()?.delete(id).await,
+        }
+    }
+
+    pub(crate) async fn list(&self, tier: ChunkTier) -> io::Result<Vec<(Uuid, SystemTime)>> {
+        match tier {
+            ChunkTier::Database => Err(unconfigured(tier)),
+            ChunkTier::Filesystem => self.filesystem()?.list().await,
+            ChunkTier::Erasure => self.erasure()?.list().await,
+        }
+    }
+}
+
+fn unconfigured(tier: ChunkTier) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{tier:?} tier is not configured for chunk storage"),
+    )
+}
+
+async fn write_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let mut file = fs::File::create(&temp).await?;
+    file.write_all(data).await?;
+    file.sync_all().await?;
+    fs::rename(temp, path).await
+}
+
+async fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
+
+async fn list_files(root: &Path) -> io::Result<Vec<(String, SystemTime)>> {
+    let mut files = vec![];
+
+    let mut dirs = match fs::read_dir(root).await {
+        Ok(dirs) => dirs,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(files),
+        Err(err) => Err(err)?,
+    };
+    while let Some(dir) = dirs.next_entry().await? {
+        if !dir.file_type().await?.is_dir() {
+            continue;
+        }
+
+        let mut entries = fs::read_dir(dir.path()).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(name) = entry.file_name().to_str() {
+                files.push((name.to_owned(), entry.metadata().await?.modified()?));
+            }
+        }
+    }
+
+    Ok(files)
+}
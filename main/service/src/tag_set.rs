@@ -9,6 +9,10 @@ use sea_query::*;
 use crate::TagMutation;
 use crate::error::DbRes;
 
+fn eq_or_null(column: tag_set::Column, value: Option<Uuid>) -> SimpleExpr {
+    value.map_or_else(|| column.is_null(), |value| column.eq(value))
+}
+
 pub struct TagSetQuery;
 
 impl TagSetQuery {
@@ -19,9 +23,9 @@ impl TagSetQuery {
         version_id: Option<Uuid>,
     ) -> DbRes<Option<tag_set::Model>> {
         TagSet::find()
-            .filter(tag_set::Column::BucketId.eq(bucket_id))
-            .filter(tag_set::Column::UploadId.eq(upload_id))
-            .filter(tag_set::Column::VersionId.eq(version_id))
+            .filter(eq_or_null(tag_set::Column::BucketId, bucket_id))
+            .filter(eq_or_null(tag_set::Column::UploadId, upload_id))
+            .filter(eq_or_null(tag_set::Column::VersionId, version_id))
             .one(db)
             .await
     }
@@ -84,9 +88,9 @@ impl TagSetMutation {
         version_id: Option<Uuid>,
     ) -> DbRes<Option<tag_set::Model>> {
         TagSet::delete_many()
-            .filter(tag_set::Column::BucketId.eq(bucket_id))
-            .filter(tag_set::Column::UploadId.eq(upload_id))
-            .filter(tag_set::Column::VersionId.eq(version_id))
+            .filter(eq_or_null(tag_set::Column::BucketId, bucket_id))
+            .filter(eq_or_null(tag_set::Column::UploadId, upload_id))
+            .filter(eq_or_null(tag_set::Column::VersionId, version_id))
             .exec_with_streaming(db)
             .await?
             .try_next()
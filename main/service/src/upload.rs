@@ -53,18 +53,36 @@ impl UploadMutation {
         bucket_id: Uuid,
         key: String,
         mime: Option<&Mime>,
+        storage_class: Option<&str>,
     ) -> DbRes<upload::Model> {
         let upload = upload::ActiveModel {
             id: Set(Uuid::new_v4()),
             bucket_id: Set(bucket_id),
             key: Set(key),
             mime: Set(mime.map(ToString::to_string)),
+            storage_class: Set(storage_class.map(ToOwned::to_owned)),
             ..Default::default()
         };
 
         Upload::insert(upload).exec_with_returning(db).await
     }
 
+    pub async fn delete_many(
+        db: &impl ConnectionTrait,
+        bucket_id: Uuid,
+        key: Option<&str>,
+        before: Option<DateTimeUtc>,
+    ) -> DbRes<DeleteResult> {
+        Upload::delete_many()
+            .filter(upload::Column::BucketId.eq(bucket_id))
+            .apply_if(key, |query, key| query.filter(upload::Column::Key.eq(key)))
+            .apply_if(before, |query, before| {
+                query.filter(upload::Column::CreatedAt.lt(before))
+            })
+            .exec(db)
+            .await
+    }
+
     pub async fn delete(
         db: &(impl ConnectionTrait + StreamTrait),
         id: Uuid,
@@ -1,29 +1,16 @@
-use std::pin::pin;
-
 use async_stream::try_stream;
 use bytesize::ByteSize;
-use crc_fast::CrcAlgorithm;
-use digest::DynDigest;
-use digest::FixedOutput;
 use futures::Stream;
-use futures::StreamExt;
-use futures::TryStreamExt;
-use md5::Md5;
 use minil_entity::prelude::*;
 use minil_entity::upload_part;
 use sea_orm::prelude::*;
 use sea_orm::sea_query::OnConflict;
 use sea_orm::*;
-use sha1::Sha1;
-use sha2::Sha256;
 use tokio::io::AsyncRead;
-use tokio_util::codec::FramedRead;
 
 use crate::ChunkMutation;
 use crate::InsRes;
 use crate::error::DbRes;
-use crate::utils::ChunkDecoder;
-use crate::utils::DigestExt;
 
 pub struct UploadPartQuery;
 
@@ -76,6 +63,23 @@ impl UploadPartQuery {
             .await
     }
 
+    pub async fn sum_size_except(
+        db: &impl ConnectionTrait,
+        upload_id: Uuid,
+        number: u16,
+    ) -> DbRes<i64> {
+        let size: Option<Option<i64>> = UploadPart::find()
+            .select_only()
+            .expr(Expr::col(upload_part::Column::Size).sum().cast_as("bigint"))
+            .filter(upload_part::Column::UploadId.eq(upload_id))
+            .filter(upload_part::Column::Number.ne(number))
+            .into_tuple()
+            .one(db)
+            .await?;
+
+        Ok(size.flatten().unwrap_or_default())
+    }
+
     pub async fn find_many(
         db: &(impl ConnectionTrait + StreamTrait),
         upload_id: Uuid,
@@ -124,10 +128,14 @@ pub struct UploadPartMutation;
 impl UploadPartMutation {
     pub async fn upsert_with_chunk(
         db: &impl ConnectionTrait,
+        db_conn: &impl ConnectionTrait,
         upload_id: Uuid,
         number: u16,
+        chunk_size: ByteSize,
         read: impl AsyncRead,
     ) -> InsRes<upload_part::Model> {
+        let staged = ChunkMutation::insert_many_staged(db_conn, chunk_size, read).await?;
+
         let id = if let Some(part) = UploadPartQuery::find(db, upload_id, number).await? {
             ChunkMutation::delete_many_by_upload_part_id(db, part.id).await?;
 
@@ -136,62 +144,21 @@ impl UploadPartMutation {
             Uuid::new_v4()
         };
 
-        let decode = ChunkDecoder::with_capacity(ByteSize::mib(5).as_u64() as usize);
-        let read = FramedRead::new(read, decode)
-            .enumerate()
-            .map(|(index, chunk)| chunk.map(|chunk| (index as u64, chunk)));
-        let mut stream = pin!(read);
-
-        let mut size = 0u64;
-        let mut crc32;
-        let mut crc32_c;
-        let mut crc64_nvme;
-        let mut sha1;
-        let mut sha256;
-        let mut md5;
-        {
-            use crc_fast::Digest;
-            crc32 = Digest::new(CrcAlgorithm::Crc32IsoHdlc);
-            crc32_c = Digest::new(CrcAlgorithm::Crc32Iscsi);
-            crc64_nvme = Digest::new(CrcAlgorithm::Crc64Nvme);
-        }
-        {
-            use digest::Digest;
-            sha1 = Sha1::new();
-            sha256 = Sha256::new();
-            md5 = Md5::new();
-        }
-
-        while let Some((index, chunk)) = stream.try_next().await? {
-            let start = size;
-
-            size += chunk.len() as u64;
-            crc32.update(&chunk);
-            crc32_c.update(&chunk);
-            crc64_nvme.update(&chunk);
-            sha1.update(&chunk);
-            sha256.update(&chunk);
-            md5.update(&chunk);
-
-            let end = size.saturating_sub(1);
-            ChunkMutation::insert(db, Some(id), None, index, start, end, chunk.to_vec()).await?;
-        }
-
         let part = upload_part::ActiveModel {
             id: Set(id),
             upload_id: Set(upload_id),
             number: Set(number as i16),
-            size: Set(size as i64),
-            crc32: Set(crc32.finalize_vec()),
-            crc32_c: Set(crc32_c.finalize_vec()),
-            crc64_nvme: Set(crc64_nvme.finalize_vec()),
-            sha1: Set(sha1.finalize_fixed().to_vec()),
-            sha256: Set(sha256.finalize_fixed().to_vec()),
-            md5: Set(md5.finalize_fixed().to_vec()),
+            size: Set(staged.checksum.size as i64),
+            crc32: Set(staged.checksum.crc32),
+            crc32_c: Set(staged.checksum.crc32_c),
+            crc64_nvme: Set(staged.checksum.crc64_nvme),
+            sha1: Set(staged.checksum.sha1),
+            sha256: Set(staged.checksum.sha256),
+            md5: Set(staged.checksum.md5),
             ..Default::default()
         };
 
-        Ok(UploadPart::insert(part)
+        let part = UploadPart::insert(part)
             .on_conflict(
                 OnConflict::column(upload_part::Column::Id)
                     .update_columns([
@@ -207,6 +174,57 @@ impl UploadPartMutation {
                     .to_owned(),
             )
             .exec_with_returning(db)
-            .await?)
+            .await?;
+        ChunkMutation::update_many_upload_part_id_by_staging_id(db, staged.id, id).await?;
+
+        Ok(part)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UploadMutation;
+    use crate::utils::test_db;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_sum_size_except() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+        let upload = UploadMutation::insert(&db_conn, bucket.id, "key".to_owned(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            UploadPartQuery::sum_size_except(&db_conn, upload.id, 1)
+                .await
+                .unwrap(),
+            0
+        );
+
+        for (number, size) in [(1, 3), (2, 5), (3, 7)] {
+            UploadPartMutation::upsert_with_chunk(
+                &db_conn,
+                &db_conn,
+                upload.id,
+                number,
+                ByteSize::kib(1),
+                vec![b'a'; size].as_slice(),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            UploadPartQuery::sum_size_except(&db_conn, upload.id, 2)
+                .await
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            UploadPartQuery::sum_size_except(&db_conn, upload.id, 4)
+                .await
+                .unwrap(),
+            15
+        );
     }
 }
@@ -0,0 +1,163 @@
+use crc_fast::CrcAlgorithm;
+use digest::DynDigest;
+use digest::FixedOutput;
+use md5::Md5;
+use minil_entity::version;
+use minil_entity::version_part;
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::utils::DigestExt;
+
+#[derive(Clone)]
+pub(crate) struct Checksum {
+    pub(crate) size: u64,
+    pub(crate) crc32: Vec<u8>,
+    pub(crate) crc32_c: Vec<u8>,
+    pub(crate) crc64_nvme: Vec<u8>,
+    pub(crate) sha1: Vec<u8>,
+    pub(crate) sha256: Vec<u8>,
+    pub(crate) md5: Vec<u8>,
+}
+
+impl Checksum {
+    pub(crate) fn mismatch(&self, other: &Self) -> Option<&'static str> {
+        if self.size != other.size {
+            Some("size")
+        } else if self.crc32 != other.crc32 {
+            Some("crc32")
+        } else if self.crc32_c != other.crc32_c {
+            Some("crc32_c")
+        } else if self.crc64_nvme != other.crc64_nvme {
+            Some("crc64_nvme")
+        } else if self.sha1 != other.sha1 {
+            Some("sha1")
+        } else if self.sha256 != other.sha256 {
+            Some("sha256")
+        } else if self.md5 != other.md5 {
+            Some("md5")
+        } else {
+            None
+        }
+    }
+}
+
+impl From<&version::Model> for Checksum {
+    fn from(version: &version::Model) -> Self {
+        Self {
+            size: version.size.unwrap_or_default() as u64,
+            crc32: version.crc32.clone().unwrap_or_default(),
+            crc32_c: version.crc32_c.clone().unwrap_or_default(),
+            crc64_nvme: version.crc64_nvme.clone().unwrap_or_default(),
+            sha1: version.sha1.clone().unwrap_or_default(),
+            sha256: version.sha256.clone().unwrap_or_default(),
+            md5: version.md5.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&version_part::Model> for Checksum {
+    fn from(version_part: &version_part::Model) -> Self {
+        Self {
+            size: version_part.size as u64,
+            crc32: version_part.crc32.clone(),
+            crc32_c: version_part.crc32_c.clone(),
+            crc64_nvme: version_part.crc64_nvme.clone(),
+            sha1: version_part.sha1.clone(),
+            sha256: version_part.sha256.clone(),
+            md5: version_part.md5.clone(),
+        }
+    }
+}
+
+pub(crate) struct ChecksumDigest {
+    size: u64,
+    crc32: crc_fast::Digest,
+    crc32_c: crc_fast::Digest,
+    crc64_nvme: crc_fast::Digest,
+    sha1: Sha1,
+    sha256: Sha256,
+    md5: Md5,
+}
+
+impl ChecksumDigest {
+    pub(crate) fn new() -> Self {
+        use digest::Digest;
+
+        Self {
+            size: 0,
+            crc32: crc_fast::Digest::new(CrcAlgorithm::Crc32IsoHdlc),
+            crc32_c: crc_fast::Digest::new(CrcAlgorithm::Crc32Iscsi),
+            crc64_nvme: crc_fast::Digest::new(CrcAlgorithm::Crc64Nvme),
+            sha1: Sha1::new(),
+            sha256: Sha256::new(),
+            md5: Md5::new(),
+        }
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        self.crc32.update(data);
+        self.crc32_c.update(data);
+        self.crc64_nvme.update(data);
+        DynDigest::update(&mut self.sha1, data);
+        DynDigest::update(&mut self.sha256, data);
+        DynDigest::update(&mut self.md5, data);
+    }
+
+    pub(crate) fn finalize(self) -> Checksum {
+        Checksum {
+            size: self.size,
+            crc32: self.crc32.finalize_vec(),
+            crc32_c: self.crc32_c.finalize_vec(),
+            crc64_nvme: self.crc64_nvme.finalize_vec(),
+            sha1: self.sha1.finalize_fixed().to_vec(),
+            sha256: self.sha256.finalize_fixed().to_vec(),
+            md5: self.md5.finalize_fixed().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum(data: &[u8]) -> Checksum {
+        let mut digest = ChecksumDigest::new();
+        digest.update(data);
+        digest.finalize()
+    }
+
+    #[test]
+    fn test_finalize() {
+        let mut digest = ChecksumDigest::new();
+        digest.update(b"12345");
+        digest.update(b"6789");
+        let checksum = digest.finalize();
+
+        assert_eq!(checksum.size, 9);
+        assert_eq!(hex::encode(&checksum.crc32), "cbf43926");
+        assert_eq!(hex::encode(&checksum.crc32_c), "e3069283");
+        assert_eq!(
+            hex::encode(&checksum.md5),
+            "25f9e794323b453885f5181f1b624d0b"
+        );
+    }
+
+    #[test]
+    fn test_mismatch() {
+        assert_eq!(checksum(b"data").mismatch(&checksum(b"data")), None);
+        assert_eq!(
+            checksum(b"data").mismatch(&checksum(b"more")),
+            Some("crc32")
+        );
+        assert_eq!(
+            checksum(b"data").mismatch(&checksum(b"datum")),
+            Some("size")
+        );
+    }
+}
@@ -1,8 +1,13 @@
 use mime::Mime;
 
+mod checksum_digest;
 mod chunk_decoder;
 mod digest_ext;
+#[cfg(test)]
+pub(super) mod test_db;
 
+pub(super) use checksum_digest::Checksum;
+pub(super) use checksum_digest::ChecksumDigest;
 pub(super) use chunk_decoder::ChunkDecoder;
 pub(super) use digest_ext::DigestExt;
 
@@ -0,0 +1,45 @@
+use std::env;
+
+use minil_entity::bucket;
+use minil_entity::owner;
+use minil_migration::Migrator;
+use minil_migration::MigratorTrait;
+use sea_orm::Database;
+use sea_orm::DbConn;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::BucketMutation;
+use crate::OwnerMutation;
+
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+pub(crate) async fn connect() -> DbConn {
+    let url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+    let db_conn = Database::connect(url)
+        .await
+        .expect("failed to connect database");
+    MIGRATED
+        .get_or_init(async || {
+            Migrator::up(&db_conn, None)
+                .await
+                .expect("failed to apply migrations");
+        })
+        .await;
+
+    db_conn
+}
+
+pub(crate) async fn create_bucket(db_conn: &DbConn) -> (owner::Model, bucket::Model) {
+    let name = Uuid::new_v4().simple().to_string();
+    let owner = OwnerMutation::insert(db_conn, name.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    let bucket = BucketMutation::insert(db_conn, owner.id, name)
+        .await
+        .unwrap()
+        .unwrap();
+
+    (owner, bucket)
+}
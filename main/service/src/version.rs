@@ -1,3 +1,9 @@
+use std::io;
+use std::io::Cursor;
+use std::mem;
+use std::pin::pin;
+
+use bytesize::ByteSize;
 use crc_fast::CrcAlgorithm;
 use crc_fast::checksum_combine;
 use digest::DynDigest;
@@ -6,19 +12,40 @@ use futures::Stream;
 use futures::TryStreamExt;
 use md5::Md5;
 use mime::Mime;
+use minil_entity::chunk;
 use minil_entity::object;
 use minil_entity::prelude::*;
+use minil_entity::sea_orm_active_enums::ChunkTier;
 use minil_entity::upload_part;
 use minil_entity::version;
+use minil_entity::version_part;
 use sea_orm::prelude::*;
 use sea_orm::*;
 use sea_orm_ext::prelude::*;
 use sea_query::*;
 use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
 
+use crate::ChunkMutation;
+use crate::CorruptVersionMutation;
 use crate::InsRes;
 use crate::VersionPartMutation;
+use crate::VersionPartQuery;
+use crate::chunk::StagedChunks;
 use crate::error::DbRes;
+use crate::utils::Checksum;
+use crate::utils::ChecksumDigest;
+
+fn crc_combine(algorithm: CrcAlgorithm, crc1: &[u8], crc2: &[u8], len2: u64) -> Vec<u8> {
+    let to_u64 = |crc: &[u8]| {
+        let mut bytes = [0; 8];
+        bytes[8 - crc.len()..].copy_from_slice(crc);
+        u64::from_be_bytes(bytes)
+    };
+
+    let crc = checksum_combine(algorithm, to_u64(crc1), to_u64(crc2), len2);
+    crc.to_be_bytes()[8 - crc2.len()..].to_vec()
+}
 
 pub struct VersionQuery;
 
@@ -65,28 +92,142 @@ impl VersionQuery {
             .stream_both(db)
             .await
     }
+
+    pub async fn find_many_ids_tierable(
+        db: &impl ConnectionTrait,
+        tier: ChunkTier,
+        before: Option<DateTimeUtc>,
+        min_size: Option<u64>,
+        noncurrent: bool,
+        storage_classes: &[String],
+        limit: u64,
+    ) -> DbRes<Vec<Uuid>> {
+        let standard = storage_classes
+            .iter()
+            .any(|storage_class| storage_class == "STANDARD");
+
+        Version::find()
+            .select_only()
+            .column(version::Column::Id)
+            .inner_join(Object)
+            .filter(version::Column::PartsCount.is_not_null())
+            .filter(version::Column::Data.is_null())
+            .apply_if(min_size, |query, min_size| {
+                query.filter(version::Column::Size.gte(min_size))
+            })
+            .filter(
+                Condition::any()
+                    .add_option(before.map(|before| version::Column::CreatedAt.lte(before)))
+                    .add_option(noncurrent.then(|| {
+                        Expr::col((Object, object::Column::VersionId))
+                            .ne(Expr::col((Version, version::Column::Id)))
+                    }))
+                    .add_option(
+                        (!storage_classes.is_empty())
+                            .then(|| version::Column::StorageClass.is_in(storage_classes)),
+                    )
+                    .add_option(standard.then(|| version::Column::StorageClass.is_null())),
+            )
+            .filter(
+                version::Column::Id.in_subquery(
+                    Query::select()
+                        .column((VersionPart, version_part::Column::VersionId))
+                        .from(VersionPart)
+                        .inner_join(
+                            Chunk,
+                            Expr::col((Chunk, chunk::Column::VersionPartId))
+                                .equals((VersionPart, version_part::Column::Id)),
+                        )
+                        .and_where(Expr::col((Chunk, chunk::Column::Tier)).ne(tier))
+                        .to_owned(),
+                ),
+            )
+            .order_by_asc(version::Column::CreatedAt)
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    pub async fn find(db: &impl ConnectionTrait, id: Uuid) -> DbRes<Option<version::Model>> {
+        Version::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_for_update(
+        db: &impl ConnectionTrait,
+        id: Uuid,
+    ) -> DbRes<Option<version::Model>> {
+        Version::find_by_id(id).lock_exclusive().one(db).await
+    }
+
+    pub async fn find_many_after(
+        db: &impl ConnectionTrait,
+        after: Option<Uuid>,
+        limit: u64,
+    ) -> DbRes<Vec<version::Model>> {
+        Version::find()
+            .filter(version::Column::PartsCount.is_not_null())
+            .filter(version::Column::Path.is_null())
+            .apply_if(after, |query, after| {
+                query.filter(version::Column::Id.gt(after))
+            })
+            .order_by_asc(version::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
 }
 
 pub struct VersionMutation;
 
 impl VersionMutation {
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn upsert_version_also_part(
         db: &impl ConnectionTrait,
+        db_conn: &impl ConnectionTrait,
         id: Option<Uuid>,
         object_id: Uuid,
         versioning: bool,
         mime: Option<&Mime>,
+        storage_class: Option<&str>,
+        chunk_size: ByteSize,
+        inline_threshold: ByteSize,
         read: impl AsyncRead,
     ) -> InsRes<version::Model> {
+        let mut read = pin!(read);
+        let mut data = vec![];
+        read.as_mut()
+            .take(inline_threshold.as_u64())
+            .read_to_end(&mut data)
+            .await?;
+
+        let staged = if (data.len() as u64) < inline_threshold.as_u64() {
+            None
+        } else {
+            let read = Cursor::new(mem::take(&mut data)).chain(read);
+            Some(ChunkMutation::insert_many_staged(db_conn, chunk_size, read).await?)
+        };
+
         let id = if let Some(id) = id {
             VersionPartMutation::delete_many(db, id).await?;
+            CorruptVersionMutation::delete_by_version_id(db, id).await?;
 
             id
         } else {
             Uuid::new_v4()
         };
 
-        let part = VersionPartMutation::insert_with_chunk(db, id, read).await?;
+        let (checksum, data) = if let Some(staged) = staged {
+            let checksum = staged.checksum.clone();
+            VersionPartMutation::insert_with_staged_chunk(db, id, 1, 0, staged).await?;
+
+            (checksum, None)
+        } else {
+            let mut digest = ChecksumDigest::new();
+            digest.update(&data);
+
+            (digest.finalize(), Some(data))
+        };
 
         let version = version::ActiveModel {
             id: Set(id),
@@ -94,13 +235,15 @@ impl VersionMutation {
             versioning: Set(versioning),
             parts_count: Set(Some(0)),
             mime: Set(mime.map(ToString::to_string)),
-            size: Set(Some(part.size)),
-            crc32: Set(Some(part.crc32)),
-            crc32_c: Set(Some(part.crc32_c)),
-            crc64_nvme: Set(Some(part.crc64_nvme)),
-            sha1: Set(Some(part.sha1)),
-            sha256: Set(Some(part.sha256)),
-            md5: Set(Some(part.md5)),
+            storage_class: Set(storage_class.map(ToOwned::to_owned)),
+            size: Set(Some(checksum.size as i64)),
+            crc32: Set(Some(checksum.crc32)),
+            crc32_c: Set(Some(checksum.crc32_c)),
+            crc64_nvme: Set(Some(checksum.crc64_nvme)),
+            sha1: Set(Some(checksum.sha1)),
+            sha256: Set(Some(checksum.sha256)),
+            md5: Set(Some(checksum.md5)),
+            data: Set(data),
             ..Default::default()
         };
 
@@ -112,6 +255,7 @@ impl VersionMutation {
                         version::Column::Versioning,
                         version::Column::PartsCount,
                         version::Column::Mime,
+                        version::Column::StorageClass,
                         version::Column::Size,
                         version::Column::Crc32,
                         version::Column::Crc32C,
@@ -120,6 +264,8 @@ impl VersionMutation {
                         version::Column::Sha256,
                         version::Column::Md5,
                         version::Column::ETag,
+                        version::Column::Data,
+                        version::Column::Path,
                     ])
                     .value(version::Column::UpdatedAt, Expr::current_timestamp())
                     .to_owned(),
@@ -136,6 +282,7 @@ impl VersionMutation {
     ) -> DbRes<version::Model> {
         let id = if let Some(id) = id {
             VersionPartMutation::delete_many(db, id).await?;
+            CorruptVersionMutation::delete_by_version_id(db, id).await?;
 
             id
         } else {
@@ -148,6 +295,7 @@ impl VersionMutation {
             versioning: Set(versioning),
             parts_count: Set(None),
             mime: Set(None),
+            storage_class: Set(None),
             size: Set(None),
             crc32: Set(None),
             crc32_c: Set(None),
@@ -156,6 +304,8 @@ impl VersionMutation {
             sha256: Set(None),
             md5: Set(None),
             e_tag: Set(None),
+            data: Set(None),
+            path: Set(None),
             ..Default::default()
         };
 
@@ -167,6 +317,7 @@ impl VersionMutation {
                         version::Column::Versioning,
                         version::Column::PartsCount,
                         version::Column::Mime,
+                        version::Column::StorageClass,
                         version::Column::Size,
                         version::Column::Crc32,
                         version::Column::Crc32C,
@@ -175,6 +326,8 @@ impl VersionMutation {
                         version::Column::Sha256,
                         version::Column::Md5,
                         version::Column::ETag,
+                        version::Column::Data,
+                        version::Column::Path,
                     ])
                     .value(version::Column::UpdatedAt, Expr::current_timestamp())
                     .to_owned(),
@@ -183,16 +336,85 @@ impl VersionMutation {
             .await
     }
 
+    pub(super) async fn upsert_mounted_also_part(
+        db: &impl ConnectionTrait,
+        id: Option<Uuid>,
+        object_id: Uuid,
+        mime: Option<&Mime>,
+        path: String,
+        checksum: Checksum,
+        modified: DateTimeUtc,
+    ) -> DbRes<version::Model> {
+        let id = if let Some(id) = id {
+            VersionPartMutation::delete_many(db, id).await?;
+            CorruptVersionMutation::delete_by_version_id(db, id).await?;
+
+            id
+        } else {
+            Uuid::new_v4()
+        };
+
+        let version = version::ActiveModel {
+            id: Set(id),
+            object_id: Set(object_id),
+            versioning: Set(false),
+            parts_count: Set(Some(0)),
+            mime: Set(mime.map(ToString::to_string)),
+            storage_class: Set(None),
+            size: Set(Some(checksum.size as i64)),
+            crc32: Set(Some(checksum.crc32)),
+            crc32_c: Set(Some(checksum.crc32_c)),
+            crc64_nvme: Set(Some(checksum.crc64_nvme)),
+            sha1: Set(Some(checksum.sha1)),
+            sha256: Set(Some(checksum.sha256)),
+            md5: Set(Some(checksum.md5)),
+            e_tag: Set(None),
+            data: Set(None),
+            path: Set(Some(path)),
+            created_at: Set(modified),
+            updated_at: Set(None),
+        };
+
+        Version::insert(version)
+            .on_conflict(
+                OnConflict::column(version::Column::Id)
+                    .target_and_where(version::Column::Versioning.eq(false))
+                    .update_columns([
+                        version::Column::Versioning,
+                        version::Column::PartsCount,
+                        version::Column::Mime,
+                        version::Column::StorageClass,
+                        version::Column::Size,
+                        version::Column::Crc32,
+                        version::Column::Crc32C,
+                        version::Column::Crc64Nvme,
+                        version::Column::Sha1,
+                        version::Column::Sha256,
+                        version::Column::Md5,
+                        version::Column::ETag,
+                        version::Column::Data,
+                        version::Column::Path,
+                        version::Column::CreatedAt,
+                        version::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await
+    }
+
     pub(super) async fn upsert_version_with_part_from_upload_parts(
         db: &(impl ConnectionTrait + StreamTrait),
         id: Option<Uuid>,
         object_id: Uuid,
         versioning: bool,
         mime: Option<&Mime>,
+        storage_class: Option<&str>,
         iter: impl Iterator<Item = upload_part::Model>,
     ) -> DbRes<version::Model> {
         let id = if let Some(id) = id {
             VersionPartMutation::delete_many(db, id).await?;
+            CorruptVersionMutation::delete_by_version_id(db, id).await?;
 
             id
         } else {
@@ -259,6 +481,7 @@ impl VersionMutation {
             versioning: Set(versioning),
             parts_count: Set(Some(parts_count as i16)),
             mime: Set(mime.map(ToString::to_string)),
+            storage_class: Set(storage_class.map(ToOwned::to_owned)),
             size: Set(Some(size as i64)),
             crc32: Set(Some(crc32_digest.to_be_bytes()[4..].to_vec())),
             crc32_c: Set(Some(crc32_c_digest.to_be_bytes()[4..].to_vec())),
@@ -268,6 +491,7 @@ impl VersionMutation {
             md5: Set(md5_digest.filter(|_| parts_count == 1)),   // fixme
             e_tag: Set((parts_count != 1)
                 .then(|| format!("\"{}-{parts_count}\"", hex::encode(e_tag.finalize_fixed())))),
+            data: Set(None),
             ..Default::default()
         };
 
@@ -279,6 +503,7 @@ impl VersionMutation {
                         version::Column::Versioning,
                         version::Column::PartsCount,
                         version::Column::Mime,
+                        version::Column::StorageClass,
                         version::Column::Size,
                         version::Column::Crc32,
                         version::Column::Crc32C,
@@ -287,6 +512,8 @@ impl VersionMutation {
                         version::Column::Sha256,
                         version::Column::Md5,
                         version::Column::ETag,
+                        version::Column::Data,
+                        version::Column::Path,
                     ])
                     .value(version::Column::UpdatedAt, Expr::current_timestamp())
                     .to_owned(),
@@ -295,6 +522,169 @@ impl VersionMutation {
             .await
     }
 
+    pub(super) async fn upsert_imported_also_part(
+        db: &impl ConnectionTrait,
+        version: version::Model,
+        parts: Vec<(version_part::Model, StagedChunks)>,
+    ) -> InsRes<version::Model> {
+        VersionPartMutation::delete_many(db, version.id).await?;
+        CorruptVersionMutation::delete_by_version_id(db, version.id).await?;
+
+        if let Some(data) = &version.data {
+            let mut digest = ChecksumDigest::new();
+            digest.update(data);
+
+            if let Some(field) = Checksum::from(&version).mismatch(&digest.finalize()) {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("version {} {field} mismatch", version.id),
+                ))?;
+            }
+        }
+
+        let mut start = 0;
+        for (part, staged) in parts {
+            if let Some(field) = Checksum::from(&part).mismatch(&staged.checksum) {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "version {} part {} {field} mismatch",
+                        version.id, part.number
+                    ),
+                ))?;
+            }
+
+            let part = VersionPartMutation::insert_with_staged_chunk(
+                db,
+                version.id,
+                part.number,
+                start,
+                staged,
+            )
+            .await?;
+            start += part.size as u64;
+        }
+
+        let version = version::ActiveModel::from(version).reset_all();
+
+        Ok(Version::insert(version)
+            .on_conflict(
+                OnConflict::column(version::Column::Id)
+                    .update_columns([
+                        version::Column::ObjectId,
+                        version::Column::Versioning,
+                        version::Column::PartsCount,
+                        version::Column::Mime,
+                        version::Column::StorageClass,
+                        version::Column::Size,
+                        version::Column::Crc32,
+                        version::Column::Crc32C,
+                        version::Column::Crc64Nvme,
+                        version::Column::Sha1,
+                        version::Column::Sha256,
+                        version::Column::Md5,
+                        version::Column::ETag,
+                        version::Column::Data,
+                        version::Column::Path,
+                        version::Column::CreatedAt,
+                        version::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await?)
+    }
+
+    pub async fn append_also_part(
+        db: &(impl ConnectionTrait + StreamTrait),
+        db_conn: &impl ConnectionTrait,
+        version: version::Model,
+        offset: u64,
+        chunk_size: ByteSize,
+        read: impl AsyncRead,
+    ) -> InsRes<Option<version::Model>> {
+        if version.path.is_some() || version.size() != offset {
+            return Ok(None);
+        }
+
+        if let Some(data) = version.data.as_ref().filter(|data| !data.is_empty()) {
+            let staged =
+                ChunkMutation::insert_many_staged(db_conn, chunk_size, data.as_slice()).await?;
+            VersionPartMutation::insert_with_staged_chunk(db, version.id, 1, 0, staged).await?;
+        }
+        let number = VersionPart::find()
+            .filter(version_part::Column::VersionId.eq(version.id))
+            .count(db)
+            .await?
+            + 1;
+
+        let staged = ChunkMutation::insert_many_staged(db_conn, chunk_size, read).await?;
+        let part = VersionPartMutation::insert_with_staged_chunk(
+            db,
+            version.id,
+            number as i16,
+            version.size(),
+            staged,
+        )
+        .await?;
+
+        let mut e_tag;
+        {
+            use digest::Digest;
+            e_tag = Md5::new();
+        }
+        {
+            let parts = VersionPartQuery::find_many_ranged(db, version.id, None).await?;
+            let mut parts = pin!(parts);
+            while let Some(part) = parts.try_next().await? {
+                e_tag.update(&part.md5);
+            }
+        }
+
+        let id = version.id;
+        let single = number == 1;
+        let size = part.size as u64;
+        let version = version::ActiveModel {
+            parts_count: Set(Some(if single { 0 } else { number as i16 })),
+            size: Set(Some((version.size() + size) as i64)),
+            crc32: Set(Some(crc_combine(
+                CrcAlgorithm::Crc32IsoHdlc,
+                version.crc32.as_deref().unwrap_or_default(),
+                &part.crc32,
+                size,
+            ))),
+            crc32_c: Set(Some(crc_combine(
+                CrcAlgorithm::Crc32Iscsi,
+                version.crc32_c.as_deref().unwrap_or_default(),
+                &part.crc32_c,
+                size,
+            ))),
+            crc64_nvme: Set(Some(crc_combine(
+                CrcAlgorithm::Crc64Nvme,
+                version.crc64_nvme.as_deref().unwrap_or_default(),
+                &part.crc64_nvme,
+                size,
+            ))),
+            sha1: Set(single.then_some(part.sha1)),
+            sha256: Set(single.then_some(part.sha256)),
+            md5: Set(single.then_some(part.md5)),
+            e_tag: Set(
+                (!single).then(|| format!("\"{}-{number}\"", hex::encode(e_tag.finalize_fixed())))
+            ),
+            data: Set(None),
+            ..Default::default()
+        };
+
+        Ok(Version::update_many()
+            .filter(version::Column::Id.eq(id))
+            .set(version)
+            .col_expr(version::Column::UpdatedAt, Expr::current_timestamp().into())
+            .exec_with_streaming(db)
+            .await?
+            .try_next()
+            .await?)
+    }
+
     pub(super) async fn delete(
         db: &(impl ConnectionTrait + StreamTrait),
         id: Uuid,
@@ -309,3 +699,190 @@ impl VersionMutation {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::ChunkCache;
+    use crate::ChunkQuery;
+    use crate::ChunkStorage;
+    use crate::ObjectMutation;
+    use crate::UploadMutation;
+    use crate::UploadPartMutation;
+    use crate::utils::test_db;
+
+    async fn append(
+        db_conn: &DbConn,
+        version: version::Model,
+        offset: u64,
+    ) -> Option<version::Model> {
+        let db_txn = db_conn.begin().await.unwrap();
+        let version = VersionMutation::append_also_part(
+            &db_txn,
+            db_conn,
+            version,
+            offset,
+            ByteSize::kib(1),
+            b"appended".as_slice(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        version
+    }
+
+    async fn read(db_conn: &DbConn, version_id: Uuid) -> Vec<u8> {
+        ChunkQuery::find_many_ranged_version_data_by_version_id(
+            db_conn.clone(),
+            Arc::new(ChunkCache::new(ByteSize::b(0))),
+            Arc::new(ChunkStorage::new(None)),
+            version_id,
+            None,
+        )
+        .map_ok(Vec::from)
+        .try_concat()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_append_also_part_inline() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &db_txn,
+            &db_conn,
+            bucket.id,
+            "inline".to_owned(),
+            false,
+            None,
+            None,
+            ByteSize::kib(1),
+            ByteSize::kib(4),
+            b"data".as_slice(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+        assert!(version.data.is_some());
+
+        let version = append(&db_conn, version, 4).await.unwrap();
+        assert_eq!(version.data, None);
+        assert_eq!(version.size, Some(12));
+        assert_eq!(version.parts_count, Some(2));
+        assert!(version.e_tag.unwrap().ends_with("-2\""));
+        assert_eq!(read(&db_conn, version.id).await, b"dataappended");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_append_also_part_multipart() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+        let upload =
+            UploadMutation::insert(&db_conn, bucket.id, "multipart".to_owned(), None, None)
+                .await
+                .unwrap();
+        let mut parts = vec![];
+        for (number, data) in [(1, b"part1"), (2, b"part2")] {
+            parts.push(
+                UploadPartMutation::upsert_with_chunk(
+                    &db_conn,
+                    &db_conn,
+                    upload.id,
+                    number,
+                    ByteSize::kib(1),
+                    data.as_slice(),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version_from_parts(
+            &db_txn,
+            bucket.id,
+            upload.key,
+            false,
+            None,
+            None,
+            parts.into_iter(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        let version = append(&db_conn, version, 10).await.unwrap();
+        assert_eq!(version.size, Some(18));
+        assert_eq!(version.parts_count, Some(3));
+        assert!(version.e_tag.unwrap().ends_with("-3\""));
+        assert_eq!(read(&db_conn, version.id).await, b"part1part2appended");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_append_also_part_wrong_offset() {
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_also_version(
+            &db_txn,
+            &db_conn,
+            bucket.id,
+            "offset".to_owned(),
+            false,
+            None,
+            None,
+            ByteSize::kib(1),
+            ByteSize::kib(4),
+            b"data".as_slice(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        for offset in [0, 3, 5] {
+            assert!(append(&db_conn, version.clone(), offset).await.is_none());
+        }
+        let version = VersionQuery::find(&db_conn, version.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.data.as_deref(), Some(&b"data"[..]));
+        assert_eq!(version.size, Some(4));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_append_also_part_mounted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mounted.txt");
+        std::fs::write(&path, "data").unwrap();
+        let db_conn = test_db::connect().await;
+        let (_, bucket) = test_db::create_bucket(&db_conn).await;
+        let db_txn = db_conn.begin().await.unwrap();
+        let (_, version) = ObjectMutation::upsert_mounted_also_version(
+            &db_txn,
+            bucket.id,
+            "mounted.txt".to_owned(),
+            &path,
+            DateTimeUtc::default(),
+        )
+        .await
+        .unwrap();
+        db_txn.commit().await.unwrap();
+
+        assert!(append(&db_conn, version.clone(), 4).await.is_none());
+        let version = VersionQuery::find(&db_conn, version.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.size, Some(4));
+        assert_eq!(version.parts_count, Some(0));
+    }
+}
@@ -1,29 +1,15 @@
 use std::ops::RangeInclusive;
-use std::pin::pin;
 
-use bytesize::ByteSize;
-use crc_fast::CrcAlgorithm;
-use digest::DynDigest;
-use digest::FixedOutput;
 use futures::Stream;
-use futures::StreamExt;
-use futures::TryStreamExt;
-use md5::Md5;
 use minil_entity::prelude::*;
 use minil_entity::upload_part;
 use minil_entity::version_part;
 use sea_orm::prelude::*;
 use sea_orm::*;
-use sha1::Sha1;
-use sha2::Sha256;
-use tokio::io::AsyncRead;
-use tokio_util::codec::FramedRead;
 
 use crate::ChunkMutation;
-use crate::InsRes;
+use crate::chunk::StagedChunks;
 use crate::error::DbRes;
-use crate::utils::ChunkDecoder;
-use crate::utils::DigestExt;
 
 pub struct VersionPartQuery;
 
@@ -40,7 +26,7 @@ impl VersionPartQuery {
             .await
     }
 
-    pub(super) async fn find_many_ranged(
+    pub async fn find_many_ranged(
         db: &(impl ConnectionTrait + StreamTrait),
         version_id: Uuid,
         range: Option<&RangeInclusive<u64>>,
@@ -61,71 +47,35 @@ impl VersionPartQuery {
 pub struct VersionPartMutation;
 
 impl VersionPartMutation {
-    pub(super) async fn insert_with_chunk(
+    pub(super) async fn insert_with_staged_chunk(
         db: &impl ConnectionTrait,
         version_id: Uuid,
-        read: impl AsyncRead,
-    ) -> InsRes<version_part::Model> {
+        number: i16,
+        start: u64,
+        staged: StagedChunks,
+    ) -> DbRes<version_part::Model> {
         let id = Uuid::new_v4();
 
-        let decode = ChunkDecoder::with_capacity(ByteSize::mib(5).as_u64() as usize);
-        let read = FramedRead::new(read, decode)
-            .enumerate()
-            .map(|(index, chunk)| chunk.map(|chunk| (index as u64, chunk)));
-        let mut stream = pin!(read);
-
-        let mut size = 0u64;
-        let mut crc32;
-        let mut crc32_c;
-        let mut crc64_nvme;
-        let mut sha1;
-        let mut sha256;
-        let mut md5;
-        {
-            use crc_fast::Digest;
-            crc32 = Digest::new(CrcAlgorithm::Crc32IsoHdlc);
-            crc32_c = Digest::new(CrcAlgorithm::Crc32Iscsi);
-            crc64_nvme = Digest::new(CrcAlgorithm::Crc64Nvme);
-        }
-        {
-            use digest::Digest;
-            sha1 = Sha1::new();
-            sha256 = Sha256::new();
-            md5 = Md5::new();
-        }
-
-        while let Some((index, chunk)) = stream.try_next().await? {
-            let start = size;
-
-            size += chunk.len() as u64;
-            crc32.update(&chunk);
-            crc32_c.update(&chunk);
-            crc64_nvme.update(&chunk);
-            sha1.update(&chunk);
-            sha256.update(&chunk);
-            md5.update(&chunk);
-
-            let end = size.saturating_sub(1);
-            ChunkMutation::insert(db, None, Some(id), index, start, end, chunk.to_vec()).await?;
-        }
-
         let part = version_part::ActiveModel {
             id: Set(id),
             version_id: Set(version_id),
-            number: Set(1),
-            start: Set(0),
-            end: Set(size.saturating_sub(1) as i64),
-            size: Set(size as i64),
-            crc32: Set(crc32.finalize_vec()),
-            crc32_c: Set(crc32_c.finalize_vec()),
-            crc64_nvme: Set(crc64_nvme.finalize_vec()),
-            sha1: Set(sha1.finalize_fixed().to_vec()),
-            sha256: Set(sha256.finalize_fixed().to_vec()),
-            md5: Set(md5.finalize_fixed().to_vec()),
+            number: Set(number),
+            start: Set(start as i64),
+            end: Set((start + staged.checksum.size).saturating_sub(1) as i64),
+            size: Set(staged.checksum.size as i64),
+            crc32: Set(staged.checksum.crc32),
+            crc32_c: Set(staged.checksum.crc32_c),
+            crc64_nvme: Set(staged.checksum.crc64_nvme),
+            sha1: Set(staged.checksum.sha1),
+            sha256: Set(staged.checksum.sha256),
+            md5: Set(staged.checksum.md5),
             ..Default::default()
         };
 
-        Ok(VersionPart::insert(part).exec_with_returning(db).await?)
+        let part = VersionPart::insert(part).exec_with_returning(db).await?;
+        ChunkMutation::update_many_version_part_id_by_staging_id(db, staged.id, id).await?;
+
+        Ok(part)
     }
 
     pub(super) async fn insert_many_from_upload_parts(